@@ -0,0 +1,59 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/// Invoke `$m!` once for every supported number of arguments, from 0 up to the maximum selected by the
+/// `arity-*` features.
+///
+/// For `n` arguments `$m!` is invoked as `$m!(n bindn; [args]; [init])`, where `bindn` is the name of the
+/// `Bind` method for callables taking `n` arguments, `args` lists the arguments as
+/// `(index name Generic)` groups and `init` is `args` without its last argument. For 0 arguments there is no
+/// bind method.
+macro_rules! for_each_arity {
+    ($m:ident) => {
+        $m!(0; []; []);
+        for_each_arity!(
+            @munch $m [];
+            [all()] 1 bind1 (0 arg1 Arg1)
+            [all()] 2 bind2 (1 arg2 Arg2)
+            [all()] 3 bind3 (2 arg3 Arg3)
+            [all()] 4 bind4 (3 arg4 Arg4)
+            [all()] 5 bind5 (4 arg5 Arg5)
+            [all()] 6 bind6 (5 arg6 Arg6)
+            [all()] 7 bind7 (6 arg7 Arg7)
+            [all()] 8 bind8 (7 arg8 Arg8)
+            [all()] 9 bind9 (8 arg9 Arg9)
+            [all()] 10 bind10 (9 arg10 Arg10)
+            [feature = "arity-16"] 11 bind11 (10 arg11 Arg11)
+            [feature = "arity-16"] 12 bind12 (11 arg12 Arg12)
+            [feature = "arity-16"] 13 bind13 (12 arg13 Arg13)
+            [feature = "arity-16"] 14 bind14 (13 arg14 Arg14)
+            [feature = "arity-16"] 15 bind15 (14 arg15 Arg15)
+            [feature = "arity-16"] 16 bind16 (15 arg16 Arg16)
+            [feature = "arity-32"] 17 bind17 (16 arg17 Arg17)
+            [feature = "arity-32"] 18 bind18 (17 arg18 Arg18)
+            [feature = "arity-32"] 19 bind19 (18 arg19 Arg19)
+            [feature = "arity-32"] 20 bind20 (19 arg20 Arg20)
+            [feature = "arity-32"] 21 bind21 (20 arg21 Arg21)
+            [feature = "arity-32"] 22 bind22 (21 arg22 Arg22)
+            [feature = "arity-32"] 23 bind23 (22 arg23 Arg23)
+            [feature = "arity-32"] 24 bind24 (23 arg24 Arg24)
+            [feature = "arity-32"] 25 bind25 (24 arg25 Arg25)
+            [feature = "arity-32"] 26 bind26 (25 arg26 Arg26)
+            [feature = "arity-32"] 27 bind27 (26 arg27 Arg27)
+            [feature = "arity-32"] 28 bind28 (27 arg28 Arg28)
+            [feature = "arity-32"] 29 bind29 (28 arg29 Arg29)
+            [feature = "arity-32"] 30 bind30 (29 arg30 Arg30)
+            [feature = "arity-32"] 31 bind31 (30 arg31 Arg31)
+            [feature = "arity-32"] 32 bind32 (31 arg32 Arg32)
+        );
+    };
+    (@munch $m:ident [$($done:tt)*];) => {};
+    (@munch $m:ident [$($done:tt)*]; [$($cfg:tt)*] $n:literal $bind:ident $arg:tt $($rest:tt)*) => {
+        #[cfg($($cfg)*)]
+        $m!($n $bind; [$($done)* $arg]; [$($done)*]);
+        for_each_arity!(@munch $m [$($done)* $arg]; $($rest)*);
+    };
+}
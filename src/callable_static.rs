@@ -3,328 +3,441 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
 use godot::prelude::*;
 
+use crate::closures::{self, Closure};
 use crate::{bind_inner::Bind, IntoCallable};
 
 #[derive(Debug, Clone, PartialEq)]
 /// A callable that takes a known number of arguments.
 pub struct CallableStatic<const ARGC: usize>(pub Callable);
 
-impl CallableStatic<0> {
-    /// Call the underlying callable with 0 arguments.
-    pub fn call(&self) -> Variant {
-        self.0.callv(varray![])
-    }
+macro_rules! impl_callable_static {
+    (0; []; []) => {
+        impl CallableStatic<0> {
+            /// Call the underlying callable with 0 arguments.
+            pub fn call(&self) -> Variant {
+                self.0.callv(&varray![])
+            }
+        }
+    };
+    ($n:literal $bind:ident; [$(($index:literal $arg:ident $generic:ident))*]; [$($init:tt)*]) => {
+        impl CallableStatic<$n> {
+            /// Return a copy of this callable with the last argument bound to the given argument.
+            pub fn bind<T: ToGodot + 'static>(self, arg: T) -> CallableStatic<{ $n - 1 }> {
+                self.$bind(arg)
+            }
+
+            /// Return a callable that calls this one with the first argument bound to the given argument.
+            ///
+            /// Godot has no bound callables for leading arguments, so this wraps the callable in a closure.
+            #[track_caller]
+            pub fn bind_front<T: ToGodot>(self, arg: T) -> CallableStatic<{ $n - 1 }> {
+                let arg = arg.to_variant();
+                adapt(self.0, "bind_front", move |args| {
+                    std::iter::once(arg.clone()).chain(args.iter().cloned()).collect()
+                })
+            }
+
+            #[doc = concat!(" Call the underlying callable with ", stringify!($n), " argument(s).")]
+            #[allow(clippy::too_many_arguments)]
+            pub fn call<$($generic: ToGodot),*>(&self, $($arg: $generic),*) -> Variant {
+                self.0.callv(&varray![$($arg.to_variant()),*])
+            }
+        }
+    };
 }
 
-impl CallableStatic<1> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<0> {
-        self.bind1(arg)
-    }
+for_each_arity!(impl_callable_static);
 
-    /// Call the underlying callable with 1 argument.
-    pub fn call<Arg1: ToVariant>(&self, arg1: Arg1) -> Variant {
-        self.0.callv(varray![arg1.to_variant()])
+/// Create a callable taking `ARGC` arguments, which runs `call` with them.
+///
+/// The closure is tied to `targets`, so it becomes invalid when any of them is freed.
+#[track_caller]
+fn wrap<const ARGC: usize>(
+    name: &str,
+    targets: impl IntoIterator<Item = Gd<Object>>,
+    call: impl FnMut(&[Variant]) -> Variant + 'static,
+) -> CallableStatic<ARGC> {
+    let mut closure = Closure::new(call).named(name);
+    for target in targets {
+        closure = closure.target(&target);
     }
+    CallableStatic(closure.into_slice_callable(Some(ARGC)))
 }
 
-impl CallableStatic<2> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<1> {
-        self.bind2(arg)
-    }
+/// Create a callable taking `ARGC` arguments, which calls `callable` with the arguments returned by
+/// `map_args`.
+#[track_caller]
+fn adapt<const ARGC: usize>(
+    callable: Callable,
+    name: &str,
+    map_args: impl Fn(&[Variant]) -> VariantArray + 'static,
+) -> CallableStatic<ARGC> {
+    wrap(name, callable.object(), move |args| {
+        callable.callv(&map_args(args))
+    })
+}
 
-    /// Call the underlying callable with 2 arguments.
-    pub fn call<Arg1: ToVariant, Arg2: ToVariant>(&self, arg1: Arg1, arg2: Arg2) -> Variant {
-        self.0.callv(varray![arg1.to_variant(), arg2.to_variant()])
-    }
+/// Leaves an argument open in [`CallableStatic::partial`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placeholder;
+
+/// A value to bind, or a [`Placeholder`], in [`CallableStatic::partial`].
+pub trait PartialArg {
+    /// 1 for placeholders, 0 for values.
+    const OPEN: usize;
+
+    /// The value to bind, `None` for placeholders.
+    fn into_bound(self) -> Option<Variant>;
 }
 
-impl CallableStatic<3> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<2> {
-        self.bind3(arg)
-    }
+impl PartialArg for Placeholder {
+    const OPEN: usize = 1;
 
-    /// Call the underlying callable with 3 arguments.
-    pub fn call<Arg1: ToVariant, Arg2: ToVariant, Arg3: ToVariant>(
-        &self,
-        arg1: Arg1,
-        arg2: Arg2,
-        arg3: Arg3,
-    ) -> Variant {
-        self.0.callv(varray![
-            arg1.to_variant(),
-            arg2.to_variant(),
-            arg3.to_variant()
-        ])
+    fn into_bound(self) -> Option<Variant> {
+        None
     }
 }
 
-impl CallableStatic<4> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<3> {
-        self.bind4(arg)
-    }
+impl<T: ToGodot> PartialArg for T {
+    const OPEN: usize = 0;
 
-    /// Call the underlying callable with 4 arguments.
-    pub fn call<Arg1: ToVariant, Arg2: ToVariant, Arg3: ToVariant, Arg4: ToVariant>(
-        &self,
-        arg1: Arg1,
-        arg2: Arg2,
-        arg3: Arg3,
-        arg4: Arg4,
-    ) -> Variant {
-        self.0.callv(varray![
-            arg1.to_variant(),
-            arg2.to_variant(),
-            arg3.to_variant(),
-            arg4.to_variant()
-        ])
+    fn into_bound(self) -> Option<Variant> {
+        Some(self.to_variant())
     }
 }
 
-impl CallableStatic<5> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<4> {
-        self.bind5(arg)
-    }
+/// A tuple of values and placeholders, one for every argument of a callable, see
+/// [`CallableStatic::partial`].
+pub trait PartialArgs {
+    const LEN: usize;
+    /// The number of placeholders.
+    const OPEN: usize;
 
-    /// Call the underlying callable with 5 arguments.
-    pub fn call<
-        Arg1: ToVariant,
-        Arg2: ToVariant,
-        Arg3: ToVariant,
-        Arg4: ToVariant,
-        Arg5: ToVariant,
-    >(
-        &self,
-        arg1: Arg1,
-        arg2: Arg2,
-        arg3: Arg3,
-        arg4: Arg4,
-        arg5: Arg5,
-    ) -> Variant {
-        self.0.callv(varray![
-            arg1.to_variant(),
-            arg2.to_variant(),
-            arg3.to_variant(),
-            arg4.to_variant(),
-            arg5.to_variant()
-        ])
-    }
+    fn into_bound(self) -> Vec<Option<Variant>>;
+}
+
+macro_rules! impl_partial_args {
+    ($n:literal $($bind:ident)?; [$(($index:literal $arg:ident $generic:ident))*]; [$($init:tt)*]) => {
+        impl<$($generic: PartialArg),*> PartialArgs for ($($generic,)*) {
+            const LEN: usize = $n;
+            const OPEN: usize = 0 $(+ $generic::OPEN)*;
+
+            fn into_bound(self) -> Vec<Option<Variant>> {
+                let ($($arg,)*) = self;
+                vec![$($arg.into_bound()),*]
+            }
+        }
+    };
+}
+
+for_each_arity!(impl_partial_args);
+
+/// Checks the arguments of [`CallableStatic::partial`] when it is compiled.
+struct PartialCheck<A, const ARGC: usize, const OPEN: usize>(PhantomData<A>);
+
+impl<A: PartialArgs, const ARGC: usize, const OPEN: usize> PartialCheck<A, ARGC, OPEN> {
+    const VALID: () = {
+        assert!(
+            A::LEN == ARGC,
+            "`partial` needs a value or a placeholder for every argument of the callable"
+        );
+        assert!(
+            A::OPEN == OPEN,
+            "the callable returned by `partial` must take one argument for every placeholder"
+        );
+    };
+}
+
+/// Checks the arity of [`CallableStatic::unbind`] when it is compiled.
+struct UnbindCheck<const ARGC: usize, const OUT: usize>;
+
+impl<const ARGC: usize, const OUT: usize> UnbindCheck<ARGC, OUT> {
+    const VALID: () = assert!(
+        OUT >= ARGC,
+        "the callable returned by `unbind` cannot take fewer arguments than the original"
+    );
 }
 
-impl CallableStatic<6> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<5> {
-        self.bind6(arg)
+impl<const ARGC: usize> CallableStatic<ARGC> {
+    /// Return a callable taking `OUT` arguments, which calls this one with all but the last `OUT - ARGC`
+    /// arguments, like `Callable.unbind` in gdscript.
+    ///
+    /// For instance, `refresh.unbind::<2>()` turns a `CallableStatic<0>` into a `CallableStatic<2>` that can
+    /// be connected to a signal with 2 arguments. Compilation fails if `OUT` is less than `ARGC`.
+    #[allow(clippy::let_unit_value)]
+    pub fn unbind<const OUT: usize>(self) -> CallableStatic<OUT> {
+        let () = UnbindCheck::<ARGC, OUT>::VALID;
+
+        if OUT == ARGC {
+            // Godot rejects unbinding no arguments.
+            return CallableStatic(self.0);
+        }
+        CallableStatic(self.0.unbind(OUT - ARGC))
     }
 
-    /// Call the underlying callable with 6 arguments.
-    pub fn call<
-        Arg1: ToVariant,
-        Arg2: ToVariant,
-        Arg3: ToVariant,
-        Arg4: ToVariant,
-        Arg5: ToVariant,
-        Arg6: ToVariant,
-    >(
-        &self,
-        arg1: Arg1,
-        arg2: Arg2,
-        arg3: Arg3,
-        arg4: Arg4,
-        arg5: Arg5,
-        arg6: Arg6,
-    ) -> Variant {
-        self.0.callv(varray![
-            arg1.to_variant(),
-            arg2.to_variant(),
-            arg3.to_variant(),
-            arg4.to_variant(),
-            arg5.to_variant(),
-            arg6.to_variant()
-        ])
+    /// Return a callable that calls this one with some of its arguments bound.
+    ///
+    /// `args` has an entry for every argument of this callable, either a value to bind or a [`Placeholder`].
+    /// The returned callable takes one argument per placeholder, in order. For instance, given a
+    /// `CallableStatic<3>`, `partial((Placeholder, 5, Placeholder))` returns a `CallableStatic<2>` that fixes
+    /// the middle argument. Compilation fails if the lengths do not match up.
+    ///
+    /// Godot has no bound callables for arguments other than the last ones, so this wraps the callable in a
+    /// closure.
+    #[allow(clippy::let_unit_value)]
+    #[track_caller]
+    pub fn partial<A: PartialArgs, const OPEN: usize>(self, args: A) -> CallableStatic<OPEN> {
+        let () = PartialCheck::<A, ARGC, OPEN>::VALID;
+
+        let bound = args.into_bound();
+        adapt(self.0, "partial", move |args| {
+            let mut open = args.iter();
+            bound
+                .iter()
+                .map(|bound| match bound {
+                    Some(value) => value.clone(),
+                    None => open
+                        .next()
+                        .cloned()
+                        .unwrap_or_else(|| unreachable!("the closure checks the argument count")),
+                })
+                .collect()
+        })
     }
 }
 
-impl CallableStatic<7> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<6> {
-        self.bind7(arg)
+impl<const ARGC: usize> IntoCallable<(), ()> for CallableStatic<ARGC> {
+    fn into_callable(self) -> Callable {
+        self.0
     }
+}
+
+/// Implemented by callables that can be treated as taking `ARGC` arguments, used to pick the `bind` methods
+/// that are available on a callable.
+///
+/// A `Callable` can be treated as taking any number of arguments, as its argument count is not known.
+pub trait Arity<const ARGC: usize> {}
+
+impl<const ARGC: usize> Arity<ARGC> for Callable {}
+
+impl<const ARGC: usize> Arity<ARGC> for CallableStatic<ARGC> {}
+
+/// Why a callable could not be converted to a [`CallableStatic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallableStaticError {
+    /// The callable is null.
+    Null,
+    /// The object the callable calls a method on has been freed.
+    ObjectFreed,
+    /// The callable is not valid for another reason.
+    Invalid,
+    /// The object of the callable has no method with the name of the callable.
+    MethodNotFound { class: String, method: String },
+    /// The callable does not take the expected number of arguments. It takes at least `min` arguments, and at
+    /// most `max` if it is not variadic.
+    ArgumentCount {
+        expected: usize,
+        min: usize,
+        max: Option<usize>,
+    },
+}
 
-    /// Call the underlying callable with 7 arguments.
-    pub fn call<
-        Arg1: ToVariant,
-        Arg2: ToVariant,
-        Arg3: ToVariant,
-        Arg4: ToVariant,
-        Arg5: ToVariant,
-        Arg6: ToVariant,
-        Arg7: ToVariant,
-    >(
-        &self,
-        arg1: Arg1,
-        arg2: Arg2,
-        arg3: Arg3,
-        arg4: Arg4,
-        arg5: Arg5,
-        arg6: Arg6,
-        arg7: Arg7,
-    ) -> Variant {
-        self.0.callv(varray![
-            arg1.to_variant(),
-            arg2.to_variant(),
-            arg3.to_variant(),
-            arg4.to_variant(),
-            arg5.to_variant(),
-            arg6.to_variant(),
-            arg7.to_variant()
-        ])
+impl fmt::Display for CallableStaticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => write!(f, "the callable is null"),
+            Self::ObjectFreed => write!(f, "the object of the callable has been freed"),
+            Self::Invalid => write!(f, "the callable is not valid"),
+            Self::MethodNotFound { class, method } => {
+                write!(f, "`{class}` has no method named `{method}`")
+            }
+            Self::ArgumentCount {
+                expected,
+                min,
+                max: Some(max),
+            } if min == max => write!(
+                f,
+                "expected the callable to take {expected} arguments, but it takes {min}"
+            ),
+            Self::ArgumentCount {
+                expected,
+                min,
+                max: Some(max),
+            } => write!(
+                f,
+                "expected the callable to take {expected} arguments, but it takes {min} to {max}"
+            ),
+            Self::ArgumentCount {
+                expected,
+                min,
+                max: None,
+            } => write!(
+                f,
+                "expected the callable to take {expected} arguments, but it takes at least {min}"
+            ),
+        }
     }
 }
 
-impl CallableStatic<8> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<7> {
-        self.bind8(arg)
+impl Error for CallableStaticError {}
+
+/// `MethodFlags::METHOD_FLAG_VARARG`.
+const METHOD_FLAG_VARARG: i64 = 16;
+
+/// The number of arguments a method takes, from its entry in `Object::get_method_list`.
+struct MethodArgs {
+    args: i64,
+    default_args: i64,
+    vararg: bool,
+}
+
+/// Look up a method of `object`, which includes the methods of its class from the ClassDB and of its script.
+fn find_method(object: &Gd<Object>, method: &StringName) -> Option<MethodArgs> {
+    let count = |info: &Dictionary, key: &str| {
+        info.get(GString::from(key))
+            .and_then(|array| array.try_to::<VariantArray>().ok())
+            .map_or(0, |array| array.len() as i64)
+    };
+
+    object
+        .get_method_list()
+        .iter_shared()
+        .find(|info| {
+            info.get(GString::from("name"))
+                .and_then(|name| name.try_to::<StringName>().ok())
+                .as_ref()
+                == Some(method)
+        })
+        .map(|info| MethodArgs {
+            args: count(&info, "args"),
+            default_args: count(&info, "default_args"),
+            vararg: info
+                .get(GString::from("flags"))
+                .and_then(|flags| flags.try_to::<i64>().ok())
+                .is_some_and(|flags| flags & METHOD_FLAG_VARARG != 0),
+        })
+}
+
+/// Check that `callable` can be called with `argc` arguments.
+///
+/// Method callables are checked against the method of their object, accounting for default arguments and
+/// arguments that were bound or unbound. Custom callables, such as closures and lambdas, are checked against
+/// the argument count they report, which already accounts for bound arguments. Custom callables that do not
+/// know their argument count are always accepted.
+fn check_argument_count(callable: &Callable, argc: usize) -> Result<(), CallableStaticError> {
+    if callable.is_null() {
+        return Err(CallableStaticError::Null);
+    }
+    if callable.object_id().is_some() && callable.object().is_none() {
+        return Err(CallableStaticError::ObjectFreed);
     }
 
-    /// Call the underlying callable with 8 arguments.
-    pub fn call<
-        Arg1: ToVariant,
-        Arg2: ToVariant,
-        Arg3: ToVariant,
-        Arg4: ToVariant,
-        Arg5: ToVariant,
-        Arg6: ToVariant,
-        Arg7: ToVariant,
-        Arg8: ToVariant,
-    >(
-        &self,
-        arg1: Arg1,
-        arg2: Arg2,
-        arg3: Arg3,
-        arg4: Arg4,
-        arg5: Arg5,
-        arg6: Arg6,
-        arg7: Arg7,
-        arg8: Arg8,
-    ) -> Variant {
-        self.0.callv(varray![
-            arg1.to_variant(),
-            arg2.to_variant(),
-            arg3.to_variant(),
-            arg4.to_variant(),
-            arg5.to_variant(),
-            arg6.to_variant(),
-            arg7.to_variant(),
-            arg8.to_variant()
-        ])
+    if callable.is_custom() {
+        if !callable.is_valid() {
+            return Err(CallableStaticError::Invalid);
+        }
+        return match closures::argument_count(callable) {
+            Some(count) if count != argc => Err(CallableStaticError::ArgumentCount {
+                expected: argc,
+                min: count,
+                max: Some(count),
+            }),
+            _ => Ok(()),
+        };
     }
-}
 
-impl CallableStatic<9> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<8> {
-        self.bind9(arg)
+    let (Some(object), Some(method)) = (callable.object(), callable.method_name()) else {
+        return Err(CallableStaticError::Invalid);
+    };
+
+    let Some(info) = find_method(&object, &method) else {
+        return Err(CallableStaticError::MethodNotFound {
+            class: object.get_class().to_string(),
+            method: method.to_string(),
+        });
+    };
+
+    // Bound arguments fill in the last arguments of the method, unbinding makes the callable take extra
+    // arguments and reports a negative count.
+    let bound = callable.bound_args_len();
+    let min = (info.args - info.default_args - bound).max(0) as usize;
+    let max = (!info.vararg).then(|| (info.args - bound).max(0) as usize);
+
+    if argc < min || max.is_some_and(|max| argc > max) {
+        return Err(CallableStaticError::ArgumentCount {
+            expected: argc,
+            min,
+            max,
+        });
     }
+    Ok(())
+}
 
-    /// Call the underlying callable with 9 arguments.
-    pub fn call<
-        Arg1: ToVariant,
-        Arg2: ToVariant,
-        Arg3: ToVariant,
-        Arg4: ToVariant,
-        Arg5: ToVariant,
-        Arg6: ToVariant,
-        Arg7: ToVariant,
-        Arg8: ToVariant,
-        Arg9: ToVariant,
-    >(
-        &self,
-        arg1: Arg1,
-        arg2: Arg2,
-        arg3: Arg3,
-        arg4: Arg4,
-        arg5: Arg5,
-        arg6: Arg6,
-        arg7: Arg7,
-        arg8: Arg8,
-        arg9: Arg9,
-    ) -> Variant {
-        self.0.callv(varray![
-            arg1.to_variant(),
-            arg2.to_variant(),
-            arg3.to_variant(),
-            arg4.to_variant(),
-            arg5.to_variant(),
-            arg6.to_variant(),
-            arg7.to_variant(),
-            arg8.to_variant(),
-            arg9.to_variant()
-        ])
+impl<const ARGC: usize> TryFrom<Callable> for CallableStatic<ARGC> {
+    type Error = CallableStaticError;
+
+    /// Wrap a callable after checking that it is valid and can be called with `ARGC` arguments.
+    fn try_from(callable: Callable) -> Result<Self, Self::Error> {
+        check_argument_count(&callable, ARGC)?;
+        Ok(CallableStatic(callable))
     }
 }
 
-impl CallableStatic<10> {
-    /// Return a copy of this callable with the last argument bound to the given argument.
-    pub fn bind<T: ToVariant + 'static>(self, arg: T) -> CallableStatic<9> {
-        self.bind10(arg)
+impl<const ARGC: usize> CallableStatic<ARGC> {
+    /// Return a callable that calls this one and returns its result transformed by `f`.
+    #[track_caller]
+    pub fn map_result<F, R>(self, mut f: F) -> CallableStatic<ARGC>
+    where
+        F: FnMut(Variant) -> R + 'static,
+        R: ToGodot,
+    {
+        let callable = self.0;
+        wrap("map_result", callable.object(), move |args| {
+            f(callable.callv(&args.iter().cloned().collect())).to_variant()
+        })
     }
 
-    /// Call the underlying callable with 10 arguments.
-    pub fn call<
-        Arg1: ToVariant,
-        Arg2: ToVariant,
-        Arg3: ToVariant,
-        Arg4: ToVariant,
-        Arg5: ToVariant,
-        Arg6: ToVariant,
-        Arg7: ToVariant,
-        Arg8: ToVariant,
-        Arg9: ToVariant,
-        Arg10: ToVariant,
-    >(
-        &self,
-        arg1: Arg1,
-        arg2: Arg2,
-        arg3: Arg3,
-        arg4: Arg4,
-        arg5: Arg5,
-        arg6: Arg6,
-        arg7: Arg7,
-        arg8: Arg8,
-        arg9: Arg9,
-        arg10: Arg10,
-    ) -> Variant {
-        self.0.callv(varray![
-            arg1.to_variant(),
-            arg2.to_variant(),
-            arg3.to_variant(),
-            arg4.to_variant(),
-            arg5.to_variant(),
-            arg6.to_variant(),
-            arg7.to_variant(),
-            arg8.to_variant(),
-            arg9.to_variant(),
-            arg10.to_variant()
-        ])
+    /// Return a callable that calls this one, and then calls `next` with the result. It returns the result of
+    /// `next`.
+    #[track_caller]
+    pub fn then(self, next: CallableStatic<1>) -> CallableStatic<ARGC> {
+        let (first, next) = (self.0, next.0);
+        let targets = first.object().into_iter().chain(next.object());
+        wrap("then", targets, move |args| {
+            let ret = first.callv(&args.iter().cloned().collect());
+            next.callv(&varray![ret])
+        })
     }
-}
 
-impl<const ARGC: usize> IntoCallable<(), ()> for CallableStatic<ARGC> {
-    fn into_callable(self) -> Callable {
-        self.0
+    /// Return a callable that only calls this one when its arguments satisfy `predicate`, and otherwise
+    /// returns `Nil`.
+    #[track_caller]
+    pub fn filter<P>(self, mut predicate: P) -> CallableStatic<ARGC>
+    where
+        P: FnMut(&[Variant]) -> bool + 'static,
+    {
+        let callable = self.0;
+        wrap("filter", callable.object(), move |args| {
+            if predicate(args) {
+                callable.callv(&args.iter().cloned().collect())
+            } else {
+                Variant::nil()
+            }
+        })
     }
 }
 
-impl<const ARGC: usize> From<Callable> for CallableStatic<ARGC> {
-    fn from(callable: Callable) -> Self {
-        CallableStatic(callable)
+impl CallableStatic<1> {
+    /// Return a callable that calls `inner`, and then calls this one with the result. This is the same as
+    /// `inner.then(self)`.
+    #[track_caller]
+    pub fn compose<const ARGC: usize>(self, inner: CallableStatic<ARGC>) -> CallableStatic<ARGC> {
+        inner.then(self)
     }
 }
@@ -3,118 +3,1059 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
+use std::any::{Any, TypeId};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Display;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError, RwLock, TryLockError};
+use std::thread::{self, ThreadId};
+
+use super::convert;
+use super::custom_callable::{self, CustomCallable, InvokeError};
+use super::executor;
+use super::signal_inner::ConnectFlags;
 use super::IntoCallable;
-use godot::prelude::*;
+use godot::classes::Os;
+use godot::{global, prelude::*};
 
-macro_rules! impl_into_callable {
-    (@struct_def $struct_name:ident, $func:ty) => {
-        #[derive(GodotClass)]
-        #[class(base=RefCounted)]
-        pub struct $struct_name {
-            func: Box<$func>,
+/// A closure with its arguments and return value erased to variants.
+type ErasedClosure = Box<dyn FnMut(&[&Variant]) -> Result<Variant, ClosureError> + Send>;
+
+/// Why a closure callable did not call its closure.
+enum ClosureError {
+    /// Godot passed the wrong number of arguments.
+    ArgumentCount(InvokeError),
+    /// An argument could not be converted to the type the closure takes.
+    Conversion {
+        index: usize,
+        expected: &'static str,
+        actual: VariantType,
+    },
+    /// The closure ran and returned an error.
+    Returned(String),
+}
+
+impl ClosureError {
+    /// Whether the closure was called before this error happened.
+    fn closure_ran(&self) -> bool {
+        matches!(self, Self::Returned(_))
+    }
+}
+
+/// Convert the argument at `index` to the type the closure takes.
+fn convert_arg<T: FromGodot>(index: usize, arg: &Variant) -> Result<T, ClosureError> {
+    convert::convert_arg(index, arg).map_err(|err| ClosureError::Conversion {
+        index: err.index,
+        expected: err.expected,
+        actual: err.actual,
+    })
+}
+
+fn fallible_result<T: ToGodot, E: Display>(result: Result<T, E>) -> Result<Variant, ClosureError> {
+    result
+        .map(|ret| ret.to_variant())
+        .map_err(|err| ClosureError::Returned(err.to_string()))
+}
+
+/// A type-erased key identifying closure callables, see [`Closure::keyed`].
+trait ClosureKey: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    fn key_eq(&self, other: &dyn ClosureKey) -> bool;
+
+    fn key_hash(&self) -> u64;
+}
+
+impl<K: Hash + Eq + Send + Sync + 'static> ClosureKey for K {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn key_eq(&self, other: &dyn ClosureKey) -> bool {
+        other.as_any().downcast_ref::<K>() == Some(self)
+    }
+
+    fn key_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        TypeId::of::<K>().hash(&mut hasher);
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// How a closure callable describes and identifies itself.
+struct ClosureInfo {
+    name: String,
+    /// Where the closure was created.
+    location: &'static Location<'static>,
+    /// Whether `location` is part of the text godot prints for the callable.
+    show_location: bool,
+    /// The number of arguments the closure takes, `None` for variadic closures.
+    argc: Option<usize>,
+    key: Option<Box<dyn ClosureKey>>,
+    /// Objects that must still exist for the closure to be called.
+    targets: Vec<InstanceId>,
+    threads: Threads,
+}
+
+impl ClosureInfo {
+    /// Whether every target still exists, checked by id as godot may ask from any thread and creating a `Gd`
+    /// would change the reference count of the target.
+    fn targets_alive(&self) -> bool {
+        self.targets
+            .iter()
+            .all(|id| global::is_instance_id_valid(id.to_i64()))
+    }
+}
+
+/// Which threads a closure callable may be called from, see [`Closure::any_thread`] and
+/// [`Closure::queue_on_main_thread`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Threads {
+    /// Calls from other threads than the main thread report an error.
+    Main,
+    /// The closure is called on whichever thread calls the callable.
+    Any,
+    /// Calls from other threads than the main thread are queued and run on the main thread at idle time.
+    QueueOnMain,
+}
+
+/// The main thread, recorded the first time the main thread checks whether it is on the main thread.
+static MAIN_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+pub(crate) fn on_main_thread() -> bool {
+    let current = thread::current().id();
+    if let Some(&main) = MAIN_THREAD.get() {
+        return main == current;
+    }
+
+    // Only asked until the main thread has been recorded, as this runs on every call of a closure callable.
+    let os = Os::singleton();
+    let on_main = os.get_thread_caller_id() == os.get_main_thread_id();
+    if on_main {
+        let _ = MAIN_THREAD.set(current);
+    }
+    on_main
+}
+
+/// Asserts that a closure, or the arguments of a queued call, can be sent to other threads.
+///
+/// Only closures that are called from any thread are required to be `Send`, see [`Closure::any_thread`].
+/// Closure callables never call or drop any other closure outside of the main thread, and queued calls are
+/// only run and dropped on the main thread.
+struct AssertSend<F>(F);
+
+// SAFETY: see above.
+unsafe impl<F> Send for AssertSend<F> {}
+
+impl<F> AssertSend<F>
+where
+    F: FnMut(&[&Variant]) -> Result<Variant, ClosureError>,
+{
+    // A method rather than a field access, so that closures capture the whole `AssertSend`.
+    fn call(&mut self, args: &[&Variant]) -> Result<Variant, ClosureError> {
+        (self.0)(args)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A closure that godot can call as a custom callable.
+///
+/// Every call to `into_callable` creates a new closure callable. Unless it has a key, copies of the resulting
+/// callable compare equal to each other but not to any other callable. Closure callables with keys compare
+/// equal when their keys do.
+///
+/// A closure callable with target objects is invalid once any of them is freed, and calling it fails without
+/// calling the closure.
+///
+/// The closure may mutate its captures, so it cannot be called while it is already running. Such a reentrant
+/// call reports an error and returns `Nil` without calling the closure. The same happens when an argument
+/// cannot be converted to the type the closure takes, or when the closure is called from a thread it may not
+/// be called from. A closure returning `Err` reports the error and returns `Nil`.
+///
+/// A panic in the closure is caught before it reaches godot, see [`set_closure_panic_hook`].
+pub(crate) struct ClosureCallable(Arc<ClosureState>);
+
+/// The state of a closure callable, shared with calls that are queued on the main thread.
+struct ClosureState {
+    info: ClosureInfo,
+    /// `None` after a closure that runs at most once has been called.
+    func: Mutex<Option<ErasedClosure>>,
+    /// The thread that is currently running the closure, used to detect reentrant calls.
+    caller: Mutex<Option<ThreadId>>,
+    once: bool,
+}
+
+impl ClosureCallable {
+    fn new<F>(info: ClosureInfo, func: F) -> Self
+    where
+        F: FnMut(&[&Variant]) -> Result<Variant, ClosureError> + 'static,
+    {
+        Self::with_once(info, func, false)
+    }
+
+    /// Create a closure callable that becomes invalid after the closure has been called.
+    fn new_once<F>(info: ClosureInfo, func: F) -> Self
+    where
+        F: FnMut(&[&Variant]) -> Result<Variant, ClosureError> + 'static,
+    {
+        Self::with_once(info, func, true)
+    }
+
+    fn with_once<F>(info: ClosureInfo, func: F, once: bool) -> Self
+    where
+        F: FnMut(&[&Variant]) -> Result<Variant, ClosureError> + 'static,
+    {
+        let mut func = AssertSend(func);
+        Self(Arc::new(ClosureState {
+            info,
+            func: Mutex::new(Some(Box::new(move |args: &[&Variant]| func.call(args)))),
+            caller: Mutex::new(None),
+            once,
+        }))
+    }
+
+    /// Call the closure on the main thread at idle time.
+    fn queue_call(&self, args: &[&Variant]) -> Result<(), InvokeError> {
+        if let Some(argc) = self.0.info.argc {
+            if args.len() != argc {
+                return Err(InvokeError::argument_count(args.len(), argc));
+            }
         }
-    };
-    (@struct_impl $struct_name:ident, $($arg:ident: $variant:ident),*) => {
-        #[godot_api]
-        impl $struct_name {
-            #[func]
-            fn __call_closure(&self, $($arg: $variant),*) -> Variant {
-                (self.func)($($arg),*)
+
+        let first = {
+            let mut queued = lock(&QUEUED_CALLS);
+            queued.push(AssertSend(QueuedCall {
+                state: Arc::clone(&self.0),
+                args: collect_args(args),
+            }));
+            queued.len() == 1
+        };
+        if first {
+            // Captures nothing, so it does not matter which thread releases it.
+            let info = ClosureInfo {
+                name: "run_queued_calls".to_owned(),
+                location: Location::caller(),
+                show_location: false,
+                argc: Some(0),
+                key: None,
+                targets: Vec::new(),
+                threads: Threads::Any,
+            };
+            let run = ClosureCallable::new(info, |_: &[&Variant]| {
+                run_queued_calls();
+                Ok(Variant::nil())
+            });
+            custom_callable::into_callable(run).call_deferred(&[]);
+        }
+        Ok(())
+    }
+}
+
+/// A call of a closure from another thread, waiting to run on the main thread.
+struct QueuedCall {
+    state: Arc<ClosureState>,
+    args: Vec<Variant>,
+}
+
+/// The calls waiting to run on the main thread. The state of a closure is only released by the thread that
+/// drops its last reference, so queued calls are only taken and dropped on the main thread.
+static QUEUED_CALLS: Mutex<Vec<AssertSend<QueuedCall>>> = Mutex::new(Vec::new());
+
+/// Run the calls that were queued from other threads, on the main thread.
+fn run_queued_calls() {
+    let queued = std::mem::take(&mut *lock(&QUEUED_CALLS));
+    for AssertSend(QueuedCall { state, args }) in queued {
+        let args = args.iter().collect::<Vec<_>>();
+        // The only possible error is a target having been freed since the call was queued, in which case the
+        // closure is skipped like godot skips invalid callables.
+        let _ = state.call(&args);
+    }
+}
+
+impl ClosureState {
+    fn call(&self, args: &[&Variant]) -> Result<Variant, InvokeError> {
+        if !self.info.targets_alive() {
+            return Err(InvokeError::InstanceIsNull);
+        }
+
+        let current = thread::current().id();
+        if *lock(&self.caller) == Some(current) {
+            godot_error!(
+                "closure `{}` was called while it was already running",
+                self.info.name
+            );
+            return Ok(Variant::nil());
+        }
+
+        let mut func = lock(&self.func);
+        let Some(inner) = func.as_mut() else {
+            return Ok(Variant::nil());
+        };
+
+        *lock(&self.caller) = Some(current);
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| inner(args))) {
+            Ok(result) => result,
+            Err(payload) => {
+                self.report_panic(payload);
+                Ok(Variant::nil())
+            }
+        };
+        *lock(&self.caller) = None;
+
+        let ran = match &result {
+            Ok(_) => true,
+            Err(err) => err.closure_ran(),
+        };
+        if self.once && ran {
+            *func = None;
+        }
+
+        match result {
+            Ok(ret) => Ok(ret),
+            Err(ClosureError::ArgumentCount(err)) => Err(err),
+            Err(ClosureError::Conversion {
+                index,
+                expected,
+                actual,
+            }) => {
+                godot_error!(
+                    "closure `{}` created at {} expected argument {} to be `{}`, but got a value of type {:?}",
+                    self.info.name,
+                    self.info.location,
+                    index + 1,
+                    expected,
+                    actual
+                );
+                Ok(Variant::nil())
+            }
+            Err(ClosureError::Returned(message)) => {
+                godot_error!(
+                    "closure `{}` created at {} returned an error: {}",
+                    self.info.name,
+                    self.info.location,
+                    message
+                );
+                Ok(Variant::nil())
+            }
+        }
+    }
+
+    fn report_panic(&self, payload: Box<dyn Any + Send>) {
+        report_panic(&self.info.name, self.info.location, payload);
+    }
+}
+
+/// Report a panic caught while running the closure or task `name`, created at `location`.
+pub(crate) fn report_panic(
+    name: &str,
+    location: &'static Location<'static>,
+    payload: Box<dyn Any + Send>,
+) {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+
+    godot_error!("closure `{name}` created at {location} panicked: {message}");
+
+    let hook = PANIC_HOOK.read().unwrap_or_else(|err| err.into_inner());
+    if let Some(hook) = hook.as_ref() {
+        hook(&ClosurePanic {
+            name,
+            location,
+            message,
+        });
+    }
+}
+
+impl Drop for ClosureState {
+    fn drop(&mut self) {
+        let mut func = self
+            .func
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        // The closure is not necessarily `Send`, so it cannot be dropped here.
+        if func.is_some() && self.info.threads != Threads::Any && !on_main_thread() {
+            godot_warn!(
+                "closure `{}` created at {} was released outside of the main thread, its captures are leaked",
+                self.info.name,
+                self.info.location
+            );
+            std::mem::forget(func.take());
+        }
+
+        // Godot frees closure callables through an `extern "C"` function, so a panic while dropping the
+        // captures or the key must not unwind.
+        let key = self.info.key.take();
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| drop((func, key)))) {
+            self.report_panic(payload);
+        }
+    }
+}
+
+impl CustomCallable for ClosureCallable {
+    fn invoke(&self, args: &[&Variant]) -> Result<Variant, InvokeError> {
+        let info = &self.0.info;
+        match info.threads {
+            Threads::Any => {}
+            _ if on_main_thread() => {}
+            Threads::Main => {
+                godot_error!(
+                    "closure `{}` created at {} can only be called on the main thread",
+                    info.name,
+                    info.location
+                );
+                return Ok(Variant::nil());
             }
+            Threads::QueueOnMain => return self.queue_call(args).map(|()| Variant::nil()),
+        }
+
+        self.0.call(args)
+    }
+
+    fn is_valid(&self) -> bool {
+        let has_func = match self.0.func.try_lock() {
+            Ok(func) => func.is_some(),
+            Err(TryLockError::Poisoned(err)) => err.into_inner().is_some(),
+            // The closure is currently running.
+            Err(TryLockError::WouldBlock) => true,
+        };
+        has_func && self.0.info.targets_alive()
+    }
+
+    fn hash(&self) -> u32 {
+        match &self.0.info.key {
+            Some(key) => key.key_hash() as u32,
+            None => {
+                let mut hasher = DefaultHasher::new();
+                Arc::as_ptr(&self.0).hash(&mut hasher);
+                hasher.finish() as u32
+            }
+        }
+    }
+
+    fn equals(&self, other: &Self) -> bool {
+        match (&self.0.info.key, &other.0.info.key) {
+            (Some(key), Some(other_key)) => key.key_eq(other_key.as_ref()),
+            (None, None) => Arc::ptr_eq(&self.0, &other.0),
+            _ => false,
         }
+    }
+
+    fn to_godot_string(&self) -> GString {
+        let ClosureInfo {
+            name,
+            location,
+            show_location,
+            ..
+        } = &self.0.info;
+
+        if *show_location {
+            format!("{name} ({location})").into()
+        } else {
+            name.into()
+        }
+    }
+
+    fn object_id(&self) -> Option<InstanceId> {
+        self.0.info.targets.first().copied()
+    }
+
+    fn argument_count(&self) -> Option<usize> {
+        self.0.info.argc
+    }
+
+    fn report_panic(&self, payload: Box<dyn Any + Send>) {
+        self.0.report_panic(payload);
+    }
+}
+
+/// The number of arguments `callable` takes, or `None` if it is not known.
+///
+/// Godot reports an unknown argument count as 0, which is only ambiguous for custom callables that were not
+/// created by this crate, the closures of this crate report their exact count.
+pub(crate) fn argument_count(callable: &Callable) -> Option<usize> {
+    // SAFETY: `ClosureCallable` is the only type this crate creates custom callables from.
+    if let Some(closure) = unsafe { custom_callable::downcast::<ClosureCallable>(callable) } {
+        return closure.argument_count();
+    }
+    match callable.arg_len() {
+        0 if callable.is_custom() => None,
+        count => Some(count),
+    }
+}
+
+/// A panic that was caught while calling a closure callable.
+#[derive(Debug, Clone, Copy)]
+pub struct ClosurePanic<'a> {
+    /// The name of the closure that panicked.
+    pub name: &'a str,
+    /// Where the closure was created.
+    pub location: &'static Location<'static>,
+    /// The panic message, if the panic had a string payload.
+    pub message: &'a str,
+}
+
+/// A hook that is called whenever a closure callable panics.
+pub type ClosurePanicHook = Box<dyn Fn(&ClosurePanic<'_>) + Send + Sync>;
+
+static PANIC_HOOK: RwLock<Option<ClosurePanicHook>> = RwLock::new(None);
+
+/// Set a hook that is called whenever a closure callable panics, replacing any previous hook.
+///
+/// The panic is always printed as a godot error and the call returns `Nil`, the hook is called afterwards.
+pub fn set_closure_panic_hook<H>(hook: H)
+where
+    H: Fn(&ClosurePanic<'_>) + Send + Sync + 'static,
+{
+    *PANIC_HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(Box::new(hook));
+}
+
+/// Remove the hook set with [`set_closure_panic_hook`].
+pub fn take_closure_panic_hook() -> Option<ClosurePanicHook> {
+    PANIC_HOOK
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .take()
+}
+
+/// Marks the arguments of a closure that returns a `Result`, used in its [`IntoCallable`] implementation.
+///
+/// When such a closure returns `Err`, the error is printed as a godot error and the call returns `Nil`.
+pub struct Fallible<Args>(PhantomData<Args>);
+
+/// Marks the arguments of a closure that returns a future, used in its [`IntoCallable`] implementation.
+///
+/// Every call spawns the future returned by the closure as a task on the main thread and returns `Nil`, see
+/// [`spawn`](crate::executor::spawn). Such a closure can therefore only be called on the main thread.
+pub struct Async<Args>(PhantomData<Args>);
+
+/// Marks the arguments of a closure that takes all its arguments as a slice of variants, used in its
+/// [`IntoCallable`] implementation.
+///
+/// Such a closure accepts any number of arguments, so it can be connected to a signal of any arity.
+pub struct Variadic;
+
+/// Options for the callable created from a closure, set on a [`Closure`].
+struct ClosureOptions {
+    name: Option<String>,
+    location: &'static Location<'static>,
+    show_location: bool,
+    key: Option<Box<dyn ClosureKey>>,
+    targets: Vec<InstanceId>,
+    threads: Threads,
+}
+
+impl ClosureOptions {
+    /// Describe a closure of type `F`, which is named after its type unless a name was set.
+    fn into_info<F>(self, argc: Option<usize>) -> ClosureInfo {
+        ClosureInfo {
+            name: self
+                .name
+                .unwrap_or_else(|| std::any::type_name::<F>().to_owned()),
+            location: self.location,
+            show_location: self.show_location,
+            argc,
+            key: self.key,
+            targets: self.targets,
+            threads: self.threads,
+        }
+    }
+}
+
+/// A closure along with options for the callable created from it.
+///
+/// Plain closures are turned into callables as if they were wrapped in a `Closure` with the default options.
+///
+/// The resulting callables are custom callables, which godot cannot ask for a method name. Calling
+/// `get_method()` on one prints an error and returns an empty name, use its string representation to tell
+/// closures apart instead, see [`Closure::named`].
+pub struct Closure<F> {
+    func: F,
+    options: ClosureOptions,
+}
+
+impl<F> Closure<F> {
+    /// Wrap a closure, remembering where this was called as the location of the closure.
+    #[track_caller]
+    pub fn new(func: F) -> Self {
+        Self {
+            func,
+            options: ClosureOptions {
+                name: None,
+                location: Location::caller(),
+                show_location: false,
+                key: None,
+                targets: Vec::new(),
+                threads: Threads::Main,
+            },
+        }
+    }
+
+    /// Set the name of the closure, which godot prints for the callable and which is used in error messages.
+    ///
+    /// Closures are named after their rust type by default.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.options.name = Some(name.into());
+        self
+    }
+
+    /// Add the location of the closure to the text godot prints for the callable.
+    pub fn with_location(mut self) -> Self {
+        self.options.show_location = true;
+        self
+    }
+
+    /// Identify the closure by a key.
+    ///
+    /// Callables created from closures with equal keys compare equal and have the same hash, regardless of
+    /// the closures themselves. This makes it possible to disconnect a closure, or check whether it is
+    /// connected, by creating a closure with the same key. Connecting closures with equal keys using
+    /// [`ConnectFlags::REF_COUNTED`] counts them as the same connection.
+    pub fn keyed<K: Hash + Eq + Send + Sync + 'static>(mut self, key: K) -> Self {
+        self.options.key = Some(Box::new(key));
+        self
+    }
+
+    /// Tie the closure to an object, which is only referenced by its instance id.
+    ///
+    /// Once any of the objects a closure is tied to is freed, its callable is no longer valid and the closure
+    /// is no longer called. Godot also removes signal connections of the callable when the first object the
+    /// closure was tied to is freed, like it does for method callables.
+    pub fn target<T: GodotClass>(mut self, object: &Gd<T>) -> Self {
+        self.options.targets.push(object.instance_id());
+        self
+    }
+
+    /// Allow the closure to be called from any thread, for instance from tasks on the `WorkerThreadPool`.
+    ///
+    /// The closure runs on the thread that calls the callable. Calls from several threads at once run one
+    /// after another.
+    ///
+    /// By default closures may only be called on the main thread, other calls report an error.
+    pub fn any_thread(mut self) -> Self
+    where
+        F: Send,
+    {
+        self.options.threads = Threads::Any;
+        self
+    }
+
+    /// Queue calls from threads other than the main thread, and run them on the main thread at idle time.
+    ///
+    /// Such a queued call returns `Nil` right away, the arguments are kept until the closure runs.
+    pub fn queue_on_main_thread(mut self) -> Self {
+        self.options.threads = Threads::QueueOnMain;
+        self
+    }
+
+    /// Run the closure at most once, see [`once`].
+    pub fn once(self) -> Closure<Once<F>> {
+        Closure {
+            func: Once(self.func),
+            options: self.options,
+        }
+    }
+}
+
+/// Wrap a closure and give it a name, see [`Closure::named`].
+#[track_caller]
+pub fn named<F>(name: impl Into<String>, func: F) -> Closure<F> {
+    Closure::new(func).named(name)
+}
+
+/// A closure that is called at most once, see [`once`].
+pub struct Once<F>(F);
+
+/// Wrap a closure so that the callable created from it runs it at most once.
+///
+/// The first call consumes the closure along with its captures, afterwards the callable is no longer valid.
+/// Connecting it to a signal always uses [`ConnectFlags::ONE_SHOT`], so the connection is removed after the
+/// first emission.
+#[track_caller]
+pub fn once<F>(func: F) -> Closure<Once<F>> {
+    Closure::new(func).once()
+}
+
+fn collect_args(args: &[&Variant]) -> Vec<Variant> {
+    args.iter().map(|&arg| arg.clone()).collect()
+}
+
+/// Check the number of arguments and bind each one, converted to the type the closure takes, to its name.
+/// Returns early from the enclosing closure on failure.
+macro_rules! convert_args {
+    ($args:ident, $n:literal, $(($index:literal $arg:ident $generic:ident))*) => {
+        let &[$($arg),*] = $args else {
+            let err = InvokeError::argument_count($args.len(), $n);
+            return Err(ClosureError::ArgumentCount(err));
+        };
+        $(let $arg = convert_arg::<$generic>($index, $arg)?;)*
     };
-    (@into_callable $struct_name:ident, $args:tt, ($($arg_generic:ident),*), ($($arg:ident),*)) => {
-        impl<F, R, $($arg_generic),*> IntoCallable<$args, R> for F
+}
+
+macro_rules! impl_into_callable {
+    ($n:literal $($bind:ident)?; [$(($index:literal $arg:ident $generic:ident))*]; [$($init:tt)*]) => {
+        impl<F, R, $($generic),*> IntoCallable<($($generic,)*), R> for F
+        where
+            F: FnMut($($generic),*) -> R + 'static,
+            R: ToGodot + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            #[track_caller]
+            fn into_callable(self) -> Callable {
+                Closure::new(self).into_callable()
+            }
+        }
+
+        impl<F, T, E, $($generic),*> IntoCallable<Fallible<($($generic,)*)>, Result<T, E>> for F
+        where
+            F: FnMut($($generic),*) -> Result<T, E> + 'static,
+            T: ToGodot + 'static,
+            E: Display + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            #[track_caller]
+            fn into_callable(self) -> Callable {
+                Closure::new(self).into_callable()
+            }
+        }
+
+        impl<F, R, $($generic),*> IntoCallable<($($generic,)*), R> for Closure<F>
         where
-            F: Fn($($arg_generic),*) -> R + 'static,
-            R: ToVariant + 'static,
-            $($arg_generic: FromVariant + 'static),*
+            F: FnMut($($generic),*) -> R + 'static,
+            R: ToGodot + 'static,
+            $($generic: FromGodot + 'static),*
         {
             fn into_callable(self) -> Callable {
-                let func = move |$($arg: Variant),*| {
-                    self($($arg.to()),*).to_variant()
-                };
-                let obj = Gd::<$struct_name>::new($struct_name {
-                    func: Box::new(func)
+                let Closure { mut func, options } = self;
+                let info = options.into_info::<F>(Some($n));
+                let closure = ClosureCallable::new(info, move |args: &[&Variant]| {
+                    convert_args!(args, $n, $(($index $arg $generic))*);
+                    Ok(func($($arg),*).to_variant())
                 });
-                let callable = Callable::from_object_method(obj.share(), "__call_closure");
-                std::mem::forget(obj);
-                callable
+                custom_callable::into_callable(closure)
+            }
+        }
+
+        impl<F, T, E, $($generic),*> IntoCallable<Fallible<($($generic,)*)>, Result<T, E>> for Closure<F>
+        where
+            F: FnMut($($generic),*) -> Result<T, E> + 'static,
+            T: ToGodot + 'static,
+            E: Display + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            fn into_callable(self) -> Callable {
+                let Closure { mut func, options } = self;
+                let info = options.into_info::<F>(Some($n));
+                let closure = ClosureCallable::new(info, move |args: &[&Variant]| {
+                    convert_args!(args, $n, $(($index $arg $generic))*);
+                    fallible_result(func($($arg),*))
+                });
+                custom_callable::into_callable(closure)
+            }
+        }
+
+        impl<F, R, $($generic),*> IntoCallable<($($generic,)*), R> for Closure<Once<F>>
+        where
+            F: FnOnce($($generic),*) -> R + 'static,
+            R: ToGodot + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            fn into_callable(self) -> Callable {
+                let Closure { func: Once(func), options } = self;
+                let info = options.into_info::<F>(Some($n));
+                let mut func = Some(func);
+                let closure = ClosureCallable::new_once(info, move |args: &[&Variant]| {
+                    convert_args!(args, $n, $(($index $arg $generic))*);
+                    let Some(func) = func.take() else {
+                        return Ok(Variant::nil());
+                    };
+                    Ok(func($($arg),*).to_variant())
+                });
+                custom_callable::into_callable(closure)
+            }
+
+            fn required_flags() -> ConnectFlags {
+                ConnectFlags::ONE_SHOT
+            }
+        }
+
+        impl<F, T, E, $($generic),*> IntoCallable<Fallible<($($generic,)*)>, Result<T, E>>
+            for Closure<Once<F>>
+        where
+            F: FnOnce($($generic),*) -> Result<T, E> + 'static,
+            T: ToGodot + 'static,
+            E: Display + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            fn into_callable(self) -> Callable {
+                let Closure { func: Once(func), options } = self;
+                let info = options.into_info::<F>(Some($n));
+                let mut func = Some(func);
+                let closure = ClosureCallable::new_once(info, move |args: &[&Variant]| {
+                    convert_args!(args, $n, $(($index $arg $generic))*);
+                    let Some(func) = func.take() else {
+                        return Ok(Variant::nil());
+                    };
+                    fallible_result(func($($arg),*))
+                });
+                custom_callable::into_callable(closure)
+            }
+
+            fn required_flags() -> ConnectFlags {
+                ConnectFlags::ONE_SHOT
+            }
+        }
+
+        impl<F, Fut, $($generic),*> IntoCallable<Async<($($generic,)*)>, Fut> for F
+        where
+            F: FnMut($($generic),*) -> Fut + 'static,
+            Fut: Future<Output = ()> + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            #[track_caller]
+            fn into_callable(self) -> Callable {
+                Closure::new(self).into_callable()
+            }
+        }
+
+        impl<F, Fut, $($generic),*> IntoCallable<Async<($($generic,)*)>, Fut> for Closure<F>
+        where
+            F: FnMut($($generic),*) -> Fut + 'static,
+            Fut: Future<Output = ()> + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            fn into_callable(self) -> Callable {
+                let Closure { mut func, options } = self;
+                let info = options.into_info::<F>(Some($n));
+                let (name, location) = (info.name.clone(), info.location);
+                let closure = ClosureCallable::new(info, move |args: &[&Variant]| {
+                    convert_args!(args, $n, $(($index $arg $generic))*);
+                    executor::spawn_named(name.clone(), location, func($($arg),*));
+                    Ok(Variant::nil())
+                });
+                custom_callable::into_callable(closure)
+            }
+        }
+
+        impl<F, Fut, $($generic),*> IntoCallable<Async<($($generic,)*)>, Fut> for Closure<Once<F>>
+        where
+            F: FnOnce($($generic),*) -> Fut + 'static,
+            Fut: Future<Output = ()> + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            fn into_callable(self) -> Callable {
+                let Closure { func: Once(func), options } = self;
+                let info = options.into_info::<F>(Some($n));
+                let (name, location) = (info.name.clone(), info.location);
+                let mut func = Some(func);
+                let closure = ClosureCallable::new_once(info, move |args: &[&Variant]| {
+                    convert_args!(args, $n, $(($index $arg $generic))*);
+                    let Some(func) = func.take() else {
+                        return Ok(Variant::nil());
+                    };
+                    executor::spawn_named(name.clone(), location, func($($arg),*));
+                    Ok(Variant::nil())
+                });
+                custom_callable::into_callable(closure)
+            }
+
+            fn required_flags() -> ConnectFlags {
+                ConnectFlags::ONE_SHOT
             }
         }
-    };
-    (@make_var_func $($arg:ident: $variant:ident),*) => {
-        dyn Fn($($variant),*) -> Variant
-    };
-    ($struct_name:ident) => {
-        impl_into_callable!(@struct_def $struct_name, dyn Fn() -> Variant);
-        impl_into_callable!(@struct_impl $struct_name,);
-        impl_into_callable!(@into_callable $struct_name, (), (), ());
-    };
-    ($struct_name:ident, ($arg:ident, $generic:ident)) => {
-        impl_into_callable!(@struct_def $struct_name, dyn Fn(Variant) -> Variant);
-        impl_into_callable!(@struct_impl $struct_name, $arg: Variant);
-        impl_into_callable!(@into_callable $struct_name, ($generic,), ($generic), ($arg));
-    };
-    ($struct_name:ident, ($arg:ident, $generic:ident), $(($args:ident, $generics:ident)),+) => {
-        impl_into_callable!(
-            @struct_def $struct_name,
-            impl_into_callable!(@make_var_func $arg: Variant, $($args: Variant),+)
-        );
-        impl_into_callable!(
-            @struct_impl $struct_name,
-            $arg: Variant, $($args: Variant),+
-        );
-        impl_into_callable!(
-            @into_callable $struct_name,
-            ($generic, $($generics),+),
-            ($generic, $($generics),+),
-            ($arg, $($args),+)
-        );
     };
 }
 
-macro_rules! impl_into_callables {
-    () => {
-        impl_into_callable!(CallableClosure);
-    };
-    (($struct_name:ident), ($arg:ident, $generic:ident) $(,)?) => {
-        impl_into_callable!($struct_name, ($arg, $generic));
-        impl_into_callables!();
-    };
-    (($struct_name:ident, $($struct_names:ident),+), ($arg:ident, $generic:ident), $(($args:ident, $generics:ident)),+ $(,)?) => {
-        impl_into_callables!(@inner $struct_name, $(($args, $generics)),+; ($arg, $generic));
-        impl_into_callables!(($($struct_names),+), $(($args, $generics)),+);
-    };
-    (@inner $struct_name:ident, ($arg:ident, $generic:ident), $(($args:ident, $generics:ident)),+; $($t:tt)*) => {
-        impl_into_callables!(@inner $struct_name, $(($args, $generics)),+; ($arg, $generic), $($t)*);
-    };
-    (@inner $struct_name:ident, ($arg:ident, $generic:ident); $($t:tt)*) => {
-        impl_into_callable!($struct_name, ($arg, $generic), $($t)*);
-    };
+impl<F, R> IntoCallable<Variadic, R> for F
+where
+    F: FnMut(&[Variant]) -> R + 'static,
+    R: ToGodot + 'static,
+{
+    #[track_caller]
+    fn into_callable(self) -> Callable {
+        Closure::new(self).into_callable()
+    }
+}
+
+impl<F, T, E> IntoCallable<Fallible<Variadic>, Result<T, E>> for F
+where
+    F: FnMut(&[Variant]) -> Result<T, E> + 'static,
+    T: ToGodot + 'static,
+    E: Display + 'static,
+{
+    #[track_caller]
+    fn into_callable(self) -> Callable {
+        Closure::new(self).into_callable()
+    }
+}
+
+impl<F, R> IntoCallable<Variadic, R> for Closure<F>
+where
+    F: FnMut(&[Variant]) -> R + 'static,
+    R: ToGodot + 'static,
+{
+    fn into_callable(self) -> Callable {
+        self.into_slice_callable(None)
+    }
+}
+
+impl<F> Closure<F> {
+    /// Create a callable from a closure taking its arguments as a slice, which takes `argc` arguments if
+    /// known. Calls with a different number of arguments report an error without running the closure.
+    pub(crate) fn into_slice_callable<R>(self, argc: Option<usize>) -> Callable
+    where
+        F: FnMut(&[Variant]) -> R + 'static,
+        R: ToGodot + 'static,
+    {
+        let Closure { mut func, options } = self;
+        let info = options.into_info::<F>(argc);
+        let closure = ClosureCallable::new(info, move |args: &[&Variant]| {
+            if let Some(argc) = argc {
+                if args.len() != argc {
+                    let err = InvokeError::argument_count(args.len(), argc);
+                    return Err(ClosureError::ArgumentCount(err));
+                }
+            }
+            Ok(func(&collect_args(args)).to_variant())
+        });
+        custom_callable::into_callable(closure)
+    }
+}
+
+impl<F, T, E> IntoCallable<Fallible<Variadic>, Result<T, E>> for Closure<F>
+where
+    F: FnMut(&[Variant]) -> Result<T, E> + 'static,
+    T: ToGodot + 'static,
+    E: Display + 'static,
+{
+    fn into_callable(self) -> Callable {
+        let Closure { mut func, options } = self;
+        let info = options.into_info::<F>(None);
+        let closure = ClosureCallable::new(info, move |args: &[&Variant]| {
+            fallible_result(func(&collect_args(args)))
+        });
+        custom_callable::into_callable(closure)
+    }
+}
+
+impl<F, R> IntoCallable<Variadic, R> for Closure<Once<F>>
+where
+    F: FnOnce(&[Variant]) -> R + 'static,
+    R: ToGodot + 'static,
+{
+    fn into_callable(self) -> Callable {
+        let Closure {
+            func: Once(func),
+            options,
+        } = self;
+        let info = options.into_info::<F>(None);
+        let mut func = Some(func);
+        let closure = ClosureCallable::new_once(info, move |args: &[&Variant]| {
+            let Some(func) = func.take() else {
+                return Ok(Variant::nil());
+            };
+            Ok(func(&collect_args(args)).to_variant())
+        });
+        custom_callable::into_callable(closure)
+    }
+
+    fn required_flags() -> ConnectFlags {
+        ConnectFlags::ONE_SHOT
+    }
+}
+
+impl<F, T, E> IntoCallable<Fallible<Variadic>, Result<T, E>> for Closure<Once<F>>
+where
+    F: FnOnce(&[Variant]) -> Result<T, E> + 'static,
+    T: ToGodot + 'static,
+    E: Display + 'static,
+{
+    fn into_callable(self) -> Callable {
+        let Closure {
+            func: Once(func),
+            options,
+        } = self;
+        let info = options.into_info::<F>(None);
+        let mut func = Some(func);
+        let closure = ClosureCallable::new_once(info, move |args: &[&Variant]| {
+            let Some(func) = func.take() else {
+                return Ok(Variant::nil());
+            };
+            fallible_result(func(&collect_args(args)))
+        });
+        custom_callable::into_callable(closure)
+    }
+
+    fn required_flags() -> ConnectFlags {
+        ConnectFlags::ONE_SHOT
+    }
+}
+
+impl<F, Fut> IntoCallable<Async<Variadic>, Fut> for F
+where
+    F: FnMut(&[Variant]) -> Fut + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    #[track_caller]
+    fn into_callable(self) -> Callable {
+        Closure::new(self).into_callable()
+    }
+}
+
+impl<F, Fut> IntoCallable<Async<Variadic>, Fut> for Closure<F>
+where
+    F: FnMut(&[Variant]) -> Fut + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    fn into_callable(self) -> Callable {
+        let Closure { mut func, options } = self;
+        let info = options.into_info::<F>(None);
+        let (name, location) = (info.name.clone(), info.location);
+        let closure = ClosureCallable::new(info, move |args: &[&Variant]| {
+            executor::spawn_named(name.clone(), location, func(&collect_args(args)));
+            Ok(Variant::nil())
+        });
+        custom_callable::into_callable(closure)
+    }
+}
+
+impl<F, Fut> IntoCallable<Async<Variadic>, Fut> for Closure<Once<F>>
+where
+    F: FnOnce(&[Variant]) -> Fut + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    fn into_callable(self) -> Callable {
+        let Closure {
+            func: Once(func),
+            options,
+        } = self;
+        let info = options.into_info::<F>(None);
+        let (name, location) = (info.name.clone(), info.location);
+        let mut func = Some(func);
+        let closure = ClosureCallable::new_once(info, move |args: &[&Variant]| {
+            let Some(func) = func.take() else {
+                return Ok(Variant::nil());
+            };
+            executor::spawn_named(name.clone(), location, func(&collect_args(args)));
+            Ok(Variant::nil())
+        });
+        custom_callable::into_callable(closure)
+    }
+
+    fn required_flags() -> ConnectFlags {
+        ConnectFlags::ONE_SHOT
+    }
 }
 
-impl_into_callables!(
-    (
-        CallableClosure10,
-        CallableClosure9,
-        CallableClosure8,
-        CallableClosure7,
-        CallableClosure6,
-        CallableClosure5,
-        CallableClosure4,
-        CallableClosure3,
-        CallableClosure2,
-        CallableClosure1
-    ),
-    (arg10, Arg10),
-    (arg9, Arg9),
-    (arg8, Arg8),
-    (arg7, Arg7),
-    (arg6, Arg6),
-    (arg5, Arg5),
-    (arg4, Arg4),
-    (arg3, Arg3),
-    (arg2, Arg2),
-    (arg1, Arg1),
-);
+for_each_arity!(impl_into_callable);
@@ -0,0 +1,25 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use godot::prelude::*;
+
+/// An argument that could not be converted to the type it was expected to have.
+pub(crate) struct ArgConversionError {
+    pub(crate) index: usize,
+    pub(crate) expected: &'static str,
+    pub(crate) actual: VariantType,
+}
+
+/// Convert the argument at `index` to `T`.
+pub(crate) fn convert_arg<T: FromGodot>(
+    index: usize,
+    arg: &Variant,
+) -> Result<T, ArgConversionError> {
+    arg.try_to::<T>().map_err(|_| ArgConversionError {
+        index,
+        expected: std::any::type_name::<T>(),
+        actual: arg.get_type(),
+    })
+}
@@ -0,0 +1,224 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::any::Any;
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+
+use godot::prelude::*;
+use godot::sys::{self, GodotFfi};
+
+/// Why a call to a custom callable failed, mirrors godot's `Callable::CallError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InvokeError {
+    InstanceIsNull,
+    TooManyArguments { expected: usize },
+    TooFewArguments { expected: usize },
+}
+
+impl InvokeError {
+    /// Create the error for a call that passed `given` arguments when `expected` were needed.
+    pub(crate) fn argument_count(given: usize, expected: usize) -> Self {
+        if given > expected {
+            Self::TooManyArguments { expected }
+        } else {
+            Self::TooFewArguments { expected }
+        }
+    }
+
+    fn write_sys(self, r_error: &mut sys::GDExtensionCallError) {
+        let (error, argument, expected) = match self {
+            Self::InstanceIsNull => (sys::GDEXTENSION_CALL_ERROR_INSTANCE_IS_NULL, 0, 0),
+            Self::TooManyArguments { expected } => (
+                sys::GDEXTENSION_CALL_ERROR_TOO_MANY_ARGUMENTS,
+                0,
+                expected as i32,
+            ),
+            Self::TooFewArguments { expected } => (
+                sys::GDEXTENSION_CALL_ERROR_TOO_FEW_ARGUMENTS,
+                0,
+                expected as i32,
+            ),
+        };
+
+        r_error.error = error;
+        r_error.argument = argument;
+        r_error.expected = expected;
+    }
+}
+
+/// A rust value that godot can call through a custom callable.
+///
+/// Godot only compares two custom callables with [`CustomCallable::equals`] if they were created from the
+/// same type, so `other` is always of the same type as `self`.
+///
+/// Godot calls these methods through `extern "C"` functions, which catch panics and report them with
+/// [`CustomCallable::report_panic`]. The value cannot report a panic in its own `Drop` once it is gone, so its
+/// `Drop` must catch and report panics itself.
+pub(crate) trait CustomCallable: 'static {
+    /// Call this with the given arguments.
+    fn invoke(&self, args: &[&Variant]) -> Result<Variant, InvokeError>;
+
+    /// Whether this can currently be called.
+    fn is_valid(&self) -> bool {
+        true
+    }
+
+    fn hash(&self) -> u32;
+
+    fn equals(&self, other: &Self) -> bool;
+
+    /// The text godot uses when printing the callable.
+    fn to_godot_string(&self) -> GString;
+
+    /// The object this calls methods on, godot disconnects the callable from signals when the object is freed.
+    fn object_id(&self) -> Option<InstanceId> {
+        None
+    }
+
+    /// The number of arguments this takes, or `None` if it is not known.
+    fn argument_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Report a panic that was caught in one of the methods above.
+    fn report_panic(&self, payload: Box<dyn Any + Send>);
+}
+
+/// Identifies the custom callables created by this crate, godot only hands out their userdata for this token.
+static TOKEN: u8 = 0;
+
+fn token() -> *mut c_void {
+    &TOKEN as *const u8 as *mut c_void
+}
+
+/// Create a godot callable that owns `custom`, it is dropped when the last copy of the callable is.
+pub(crate) fn into_callable<C: CustomCallable>(custom: C) -> Callable {
+    let object_id = custom.object_id().map_or(0, InstanceId::to_u64);
+    let userdata = Box::into_raw(Box::new(custom));
+
+    let mut info = sys::GDExtensionCallableCustomInfo2 {
+        callable_userdata: userdata as *mut c_void,
+        token: token(),
+        object_id,
+        call_func: Some(call::<C>),
+        is_valid_func: Some(is_valid::<C>),
+        free_func: Some(free::<C>),
+        hash_func: Some(hash::<C>),
+        equal_func: Some(equal::<C>),
+        less_than_func: None,
+        to_string_func: Some(to_string::<C>),
+        get_argument_count_func: Some(get_argument_count::<C>),
+    };
+
+    // SAFETY: `info` is fully initialized, and godot takes ownership of `userdata`, releasing it through
+    // `free::<C>`.
+    unsafe {
+        Callable::new_with_uninit(|type_ptr| {
+            sys::interface_fn!(callable_custom_create2)(type_ptr, &mut info)
+        })
+    }
+}
+
+/// The value owned by `callable`, if it is a custom callable created by this crate.
+///
+/// # Safety
+///
+/// Every custom callable created by this crate must have been created by `into_callable::<C>`.
+pub(crate) unsafe fn downcast<C: CustomCallable>(callable: &Callable) -> Option<&C> {
+    let userdata = sys::interface_fn!(callable_custom_get_userdata)(callable.sys(), token());
+    // The userdata lives as long as any copy of the callable does.
+    (!userdata.is_null()).then(|| borrow_userdata::<C>(userdata))
+}
+
+/// # Safety
+///
+/// `userdata` must have been created by `into_callable::<C>` and not yet be freed.
+unsafe fn borrow_userdata<'a, C: CustomCallable>(userdata: *mut c_void) -> &'a C {
+    &*(userdata as *const C)
+}
+
+/// Run `f` for `custom`, a panic is reported through `custom` and `default` is returned instead, as unwinding
+/// into godot aborts the process.
+fn catch_panic<C: CustomCallable, R>(custom: &C, default: R, f: impl FnOnce() -> R) -> R {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        // Reporting the panic may panic as well, for instance in a panic hook.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| custom.report_panic(payload)));
+        default
+    })
+}
+
+unsafe extern "C" fn call<C: CustomCallable>(
+    userdata: *mut c_void,
+    args: *const sys::GDExtensionConstVariantPtr,
+    argc: sys::GDExtensionInt,
+    r_return: sys::GDExtensionVariantPtr,
+    r_error: *mut sys::GDExtensionCallError,
+) {
+    let custom = borrow_userdata::<C>(userdata);
+    let args: &[&Variant] = if argc == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(args as *const &Variant, argc as usize)
+    };
+
+    match catch_panic(custom, Ok(Variant::nil()), || custom.invoke(args)) {
+        Ok(ret) => {
+            *(r_return as *mut Variant) = ret;
+            (*r_error).error = sys::GDEXTENSION_CALL_OK;
+        }
+        Err(err) => err.write_sys(&mut *r_error),
+    }
+}
+
+unsafe extern "C" fn is_valid<C: CustomCallable>(userdata: *mut c_void) -> sys::GDExtensionBool {
+    let custom = borrow_userdata::<C>(userdata);
+    catch_panic(custom, false, || custom.is_valid()) as sys::GDExtensionBool
+}
+
+unsafe extern "C" fn free<C: CustomCallable>(userdata: *mut c_void) {
+    let custom = Box::from_raw(userdata as *mut C);
+    // `C` reports panics in its `Drop` itself, this only keeps anything else from unwinding into godot.
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(custom)));
+}
+
+unsafe extern "C" fn hash<C: CustomCallable>(userdata: *mut c_void) -> u32 {
+    let custom = borrow_userdata::<C>(userdata);
+    catch_panic(custom, 0, || custom.hash())
+}
+
+unsafe extern "C" fn equal<C: CustomCallable>(
+    userdata_a: *mut c_void,
+    userdata_b: *mut c_void,
+) -> sys::GDExtensionBool {
+    let a = borrow_userdata::<C>(userdata_a);
+    let b = borrow_userdata::<C>(userdata_b);
+    catch_panic(a, false, || a.equals(b)) as sys::GDExtensionBool
+}
+
+unsafe extern "C" fn to_string<C: CustomCallable>(
+    userdata: *mut c_void,
+    r_is_valid: *mut sys::GDExtensionBool,
+    r_out: sys::GDExtensionStringPtr,
+) {
+    let custom = borrow_userdata::<C>(userdata);
+    match catch_panic(custom, None, || Some(custom.to_godot_string())) {
+        Some(string) => {
+            string.move_into_string_ptr(r_out);
+            *r_is_valid = true as sys::GDExtensionBool;
+        }
+        None => *r_is_valid = false as sys::GDExtensionBool,
+    }
+}
+
+unsafe extern "C" fn get_argument_count<C: CustomCallable>(
+    userdata: *mut c_void,
+    r_is_valid: *mut sys::GDExtensionBool,
+) -> sys::GDExtensionInt {
+    let custom = borrow_userdata::<C>(userdata);
+    let count = catch_panic(custom, None, || custom.argument_count());
+    *r_is_valid = count.is_some() as sys::GDExtensionBool;
+    count.unwrap_or_default() as sys::GDExtensionInt
+}
@@ -0,0 +1,157 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::cell::RefCell;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Wake, Waker};
+
+use super::closures::{self, Closure};
+use super::signal_inner::SignalTrait;
+use godot::classes::Engine;
+use godot::prelude::*;
+
+/// A future running on the main thread, along with where it was spawned from.
+struct Task {
+    name: String,
+    location: &'static Location<'static>,
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    waker: Arc<TaskWaker>,
+}
+
+impl Task {
+    /// Poll the task if it was woken since it was last polled, returns whether it is finished.
+    fn poll(&mut self) -> bool {
+        if !self.waker.woken.swap(false, Ordering::AcqRel) {
+            return false;
+        }
+
+        let waker = Waker::from(Arc::clone(&self.waker));
+        let mut cx = Context::from_waker(&waker);
+        match panic::catch_unwind(AssertUnwindSafe(|| self.future.as_mut().poll(&mut cx))) {
+            Ok(poll) => poll.is_ready(),
+            Err(payload) => {
+                closures::report_panic(&self.name, self.location, payload);
+                true
+            }
+        }
+    }
+}
+
+/// Marks a task to be polled on the next frame, wakers may be used from any thread.
+struct TaskWaker {
+    woken: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+    }
+}
+
+thread_local! {
+    /// The tasks that have not finished yet, only ever used on the main thread.
+    static TASKS: RefCell<Vec<Task>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Identifies the closure that polls the tasks every frame.
+#[derive(Debug, Hash, PartialEq, Eq)]
+struct PollTasks;
+
+/// Poll every task that was woken, called once per frame.
+fn poll_tasks() {
+    // Tasks may spawn other tasks while they are polled, so they cannot stay borrowed.
+    let mut tasks = TASKS.with(|tasks| std::mem::take(&mut *tasks.borrow_mut()));
+    tasks.retain_mut(|task| !task.poll());
+    TASKS.with(|spawned| {
+        let mut spawned = spawned.borrow_mut();
+        tasks.append(&mut spawned);
+        *spawned = tasks;
+    });
+}
+
+/// The main loop, if it is a scene tree.
+pub(crate) fn scene_tree() -> Option<Gd<SceneTree>> {
+    Engine::singleton()
+        .get_main_loop()
+        .and_then(|main_loop| main_loop.try_cast::<SceneTree>().ok())
+}
+
+/// Make sure `poll_tasks` is connected to the `process_frame` signal of the scene tree.
+fn connect_poll_tasks() {
+    let Some(tree) = scene_tree() else {
+        godot_error!("tasks can only run while the main loop is a `SceneTree`");
+        return;
+    };
+
+    let poll = || {
+        Closure::new(poll_tasks)
+            .named("poll_tasks")
+            .keyed(PollTasks)
+    };
+    if !tree.is_callable_connected("process_frame", poll()) {
+        let _ = tree.connect_default("process_frame", poll());
+    }
+}
+
+/// Run a future on the main thread.
+///
+/// The future is polled right away, so it runs up to its first `.await` before this returns. Afterwards it is
+/// polled once per frame after it was woken, until it finishes. A panic in the future is reported like a
+/// panic in a closure, see [`set_closure_panic_hook`](crate::closures::set_closure_panic_hook).
+///
+/// # Panics
+///
+/// If this is called from another thread than the main thread.
+#[track_caller]
+pub fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + 'static,
+{
+    spawn_named(
+        std::any::type_name::<F>().to_owned(),
+        Location::caller(),
+        future,
+    );
+}
+
+/// Run a future on the main thread as the task `name`, see [`spawn`].
+pub(crate) fn spawn_named<F>(name: String, location: &'static Location<'static>, future: F)
+where
+    F: Future<Output = ()> + 'static,
+{
+    assert!(
+        closures::on_main_thread(),
+        "tasks can only be spawned on the main thread"
+    );
+
+    let mut task = Task {
+        name,
+        location,
+        future: Box::pin(future),
+        waker: Arc::new(TaskWaker {
+            woken: AtomicBool::new(true),
+        }),
+    };
+    if task.poll() {
+        return;
+    }
+
+    let first = TASKS.with(|tasks| {
+        let mut tasks = tasks.borrow_mut();
+        tasks.push(task);
+        tasks.len() == 1
+    });
+    if first {
+        connect_poll_tasks();
+    }
+}
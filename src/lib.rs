@@ -5,15 +5,34 @@
  */
 use godot::prelude::*;
 use prelude::CallableStatic;
+use signal_inner::ConnectFlags;
 
+#[macro_use]
+mod arity;
 mod bind_inner;
 pub mod callable_static;
 pub mod closures;
+mod convert;
+mod custom_callable;
+pub mod executor;
+pub mod signal_future;
 pub mod signal_inner;
+pub mod signal_stream;
+pub mod typed_callable;
+pub mod wait;
 
 pub trait IntoCallable<Args, R> {
     fn into_callable(self) -> Callable;
 
+    /// Flags that are always added when connecting this to a signal.
+    fn required_flags() -> ConnectFlags
+    where
+        Self: Sized,
+    {
+        ConnectFlags::default()
+    }
+
+    #[track_caller]
     fn into_static<const ARGC: usize>(self) -> CallableStatic<ARGC>
     where
         Self: Sized,
@@ -24,7 +43,7 @@ pub trait IntoCallable<Args, R> {
 
 impl<T: GodotClass> IntoCallable<(), ()> for (Gd<T>, &'static str) {
     fn into_callable(self) -> Callable {
-        Callable::from_object_method(self.0, self.1)
+        Callable::from_object_method(&self.0, &StringName::from(self.1))
     }
 }
 
@@ -35,11 +54,13 @@ impl IntoCallable<(), ()> for Callable {
 }
 
 pub mod bind {
-    pub use super::bind_inner::Bind as _;
+    pub use super::bind_inner::{Bind as _, BindTuple as _};
 }
 
 pub mod signal {
+    pub use super::signal_future::{SignalArgs, SignalError, SignalFuture};
     pub use super::signal_inner::{ConnectFlags, SignalProperty, SignalTrait as _};
+    pub use super::signal_stream::{SignalReceiver, SignalStream};
 }
 
 pub mod prelude {
@@ -47,5 +68,10 @@ pub mod prelude {
     pub use super::callable_static::*;
     pub use super::closures::*;
     pub use super::signal::*;
+    pub use super::typed_callable::*;
+    pub use super::wait::{
+        Cancellable, CancellationToken, Cancelled, Elapsed, Join, JoinAll, Select, SelectAll,
+        Sleep, Timeout,
+    };
     pub use super::IntoCallable;
 }
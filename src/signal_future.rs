@@ -0,0 +1,281 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll, Waker};
+
+use super::closures::{self, once, Closure};
+use super::convert;
+use super::signal_inner::{ConnectFlags, SignalTrait};
+use super::IntoCallable;
+use godot::{global, prelude::*};
+
+/// Why awaiting a signal failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignalError {
+    /// The signal could not be connected to, for instance because it does not exist.
+    Connect(global::Error),
+    /// The object was freed before the signal was emitted.
+    ObjectFreed,
+    /// The connection was removed before the signal was emitted.
+    Disconnected,
+    /// The signal was emitted with a different number of arguments than expected.
+    ArgumentCount { expected: usize, actual: usize },
+    /// An argument of the signal could not be converted to the expected type.
+    Conversion {
+        index: usize,
+        expected: &'static str,
+        actual: VariantType,
+    },
+}
+
+impl fmt::Display for SignalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect(err) => write!(f, "failed to connect to the signal: {err:?}"),
+            Self::ObjectFreed => write!(f, "the object was freed before the signal was emitted"),
+            Self::Disconnected => write!(f, "the signal was disconnected before it was emitted"),
+            Self::ArgumentCount { expected, actual } => write!(
+                f,
+                "expected the signal to have {expected} arguments, but it was emitted with {actual}"
+            ),
+            Self::Conversion {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected argument {} of the signal to be `{}`, but got a value of type {:?}",
+                index + 1,
+                expected,
+                actual
+            ),
+        }
+    }
+}
+
+impl Error for SignalError {}
+
+/// The arguments of a signal, as a tuple of types that can be converted from variants.
+pub trait SignalArgs: Sized + 'static {
+    /// The number of arguments.
+    const COUNT: usize;
+
+    /// Convert the arguments of an emission.
+    fn from_args(args: &[Variant]) -> Result<Self, SignalError>;
+}
+
+fn convert_arg<T: FromGodot>(index: usize, arg: &Variant) -> Result<T, SignalError> {
+    convert::convert_arg(index, arg).map_err(|err| SignalError::Conversion {
+        index: err.index,
+        expected: err.expected,
+        actual: err.actual,
+    })
+}
+
+macro_rules! impl_signal_args {
+    ($n:literal $($bind:ident)?; [$(($index:literal $arg:ident $generic:ident))*]; [$($init:tt)*]) => {
+        impl<$($generic: FromGodot + 'static),*> SignalArgs for ($($generic,)*) {
+            const COUNT: usize = $n;
+
+            fn from_args(args: &[Variant]) -> Result<Self, SignalError> {
+                let [$($arg),*] = args else {
+                    return Err(SignalError::ArgumentCount {
+                        expected: $n,
+                        actual: args.len(),
+                    });
+                };
+                Ok(($(convert_arg::<$generic>($index, $arg)?,)*))
+            }
+        }
+    };
+}
+
+for_each_arity!(impl_signal_args);
+
+/// Identifies the closure connected by a signal future or stream, so it can be disconnected again.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct ConnectionKey(u64);
+
+impl ConnectionKey {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A closure connected to a signal, which is disconnected when this is dropped.
+///
+/// This can be sent to other threads, dropping it outside of the main thread disconnects on the main thread
+/// at idle time.
+pub(crate) struct Connection {
+    object: InstanceId,
+    /// Kept as a `String`, as godot strings cannot be sent to other threads.
+    signal_name: String,
+    key: ConnectionKey,
+}
+
+impl Connection {
+    /// Connect `closure` to a signal of `object`.
+    ///
+    /// The closure is tied to `object`, so it is dropped when the object is freed.
+    pub(crate) fn connect<T, F, Args, R>(
+        object: &Gd<T>,
+        signal_name: StringName,
+        closure: Closure<F>,
+        flags: ConnectFlags,
+    ) -> (Self, Result<(), global::Error>)
+    where
+        T: GodotClass + Inherits<Object>,
+        Closure<F>: IntoCallable<Args, R>,
+    {
+        let key = ConnectionKey::next();
+        let closure = closure.keyed(key).target(object);
+        let result = object.connect_flags(signal_name.clone(), closure, flags);
+        let connection = Self {
+            object: object.instance_id(),
+            signal_name: signal_name.to_string(),
+            key,
+        };
+        (connection, result)
+    }
+
+    /// Whether the object the signal belongs to has been freed.
+    ///
+    /// This is checked by id, as creating a `Gd` would change the reference count of the object from whichever
+    /// thread polls the future.
+    pub(crate) fn object_freed(&self) -> bool {
+        !global::is_instance_id_valid(self.object.to_i64())
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if !closures::on_main_thread() {
+            let connection = Self {
+                object: self.object,
+                signal_name: std::mem::take(&mut self.signal_name),
+                key: self.key,
+            };
+            // Runs on the main thread, where the connection is dropped again.
+            once(move |_: &[Variant]| drop(connection))
+                .named("disconnect")
+                .any_thread()
+                .into_callable()
+                .call_deferred(&[]);
+            return;
+        }
+
+        let Ok(object) = Gd::<Object>::try_from_instance_id(self.object) else {
+            return;
+        };
+
+        // Equal to the connected closure, as closures with the same key compare equal. It is tied to the object
+        // like the connected closure, as godot looks up the connection through the object of the callable.
+        let closure = || {
+            Closure::new(|_: &[Variant]| {})
+                .keyed(self.key)
+                .target(&object)
+        };
+        let signal_name = self.signal_name.as_str();
+        if object.is_callable_connected(signal_name, closure()) {
+            object.disconnect_callable(signal_name, closure());
+        }
+    }
+}
+
+/// The state shared between a signal future and the closure connected to the signal.
+struct Shared<A> {
+    result: Option<Result<A, SignalError>>,
+    /// Whether the closure was dropped, which means it will never be called.
+    closure_dropped: bool,
+    waker: Option<Waker>,
+}
+
+/// Captured by the closure connected to the signal, wakes the future when the closure is called or dropped.
+struct Sender<A> {
+    shared: Rc<RefCell<Shared<A>>>,
+}
+
+impl<A> Sender<A> {
+    /// Store the result, the future is woken when `self` is dropped right after.
+    fn send(self, result: Result<A, SignalError>) {
+        self.shared.borrow_mut().result = Some(result);
+    }
+}
+
+impl<A> Drop for Sender<A> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut shared = self.shared.borrow_mut();
+            shared.closure_dropped = true;
+            shared.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A future that resolves with the arguments of the next emission of a signal, see
+/// [`SignalTrait::signal_future`].
+///
+/// Dropping the future before the signal is emitted disconnects from the signal.
+pub struct SignalFuture<A> {
+    shared: Rc<RefCell<Shared<A>>>,
+    connection: Connection,
+}
+
+impl<A: SignalArgs> SignalFuture<A> {
+    pub(crate) fn new<T>(object: &Gd<T>, signal_name: StringName) -> Self
+    where
+        T: GodotClass + Inherits<Object>,
+    {
+        let shared = Rc::new(RefCell::new(Shared {
+            result: None,
+            closure_dropped: false,
+            waker: None,
+        }));
+
+        let sender = Sender {
+            shared: Rc::clone(&shared),
+        };
+        let closure = once(move |args: &[Variant]| sender.send(A::from_args(args)))
+            .named(format!("await {signal_name}"));
+        let (connection, result) =
+            Connection::connect(object, signal_name, closure, ConnectFlags::ONE_SHOT);
+        if let Err(err) = result {
+            shared.borrow_mut().result = Some(Err(SignalError::Connect(err)));
+        }
+
+        Self { shared, connection }
+    }
+}
+
+impl<A: SignalArgs> Future for SignalFuture<A> {
+    type Output = Result<A, SignalError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.borrow_mut();
+        if let Some(result) = shared.result.take() {
+            return Poll::Ready(result);
+        }
+        if self.connection.object_freed() {
+            return Poll::Ready(Err(SignalError::ObjectFreed));
+        }
+        if shared.closure_dropped {
+            return Poll::Ready(Err(SignalError::Disconnected));
+        }
+
+        shared.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
@@ -3,8 +3,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
+use super::closures::{once, Closure, Once};
+use super::signal_future::{SignalArgs, SignalFuture};
+use super::signal_stream::{SignalReceiver, SignalStream};
 use super::IntoCallable;
-use godot::{engine::global, prelude::*};
+use godot::{global, prelude::*};
 
 /// Options for modifying how a connection happens.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
@@ -14,14 +17,15 @@ pub struct ConnectFlags {
     /// Persisting connections are stored when the object is serialized (such as when using
     /// PackedScene.pack). In the editor, connections created through the Node dock are always persisting.
     persist: bool,
-    /// One-shot connections disconnect themselves after emission.
+    /// One-shot connections disconnect themselves after emission. Closures wrapped in
+    /// [`once`](crate::closures::once) always connect as one-shot.
     one_shot: bool,
     /// Reference-counted connections can be assigned to the same Callable multiple times. Each disconnection
     /// decreases the internal counter. The signal fully disconnects only when the counter reaches 0.
     ref_counted: bool,
 }
 
-impl From<ConnectFlags> for i64 {
+impl From<ConnectFlags> for u32 {
     fn from(flags: ConnectFlags) -> Self {
         let ConnectFlags {
             deferred,
@@ -30,7 +34,7 @@ impl From<ConnectFlags> for i64 {
             ref_counted,
         } = flags;
 
-        deferred as i64 | (persist as i64) << 1 | (one_shot as i64) << 2 | (ref_counted as i64) << 3
+        deferred as u32 | (persist as u32) << 1 | (one_shot as u32) << 2 | (ref_counted as u32) << 3
     }
 }
 
@@ -83,21 +87,26 @@ impl ConnectFlags {
 
 /// The name and type of an argument that a signal takes.
 pub struct SignalProperty {
-    name: GodotString,
+    name: GString,
     type_: VariantType,
 }
 
-impl ToVariant for SignalProperty {
-    fn to_variant(&self) -> Variant {
+impl GodotConvert for SignalProperty {
+    type Via = Dictionary;
+}
+
+impl ToGodot for SignalProperty {
+    type ToVia<'v> = Dictionary;
+
+    fn to_godot(&self) -> Self::ToVia<'_> {
         dict! {
             "name": self.name.clone(),
-            "type": self.type_ as i64
+            "type": self.type_.ord()
         }
-        .to_variant()
     }
 }
 
-impl<S: Into<GodotString>> From<(S, VariantType)> for SignalProperty {
+impl<S: Into<GString>> From<(S, VariantType)> for SignalProperty {
     fn from((name, type_): (S, VariantType)) -> Self {
         Self {
             name: name.into(),
@@ -107,13 +116,13 @@ impl<S: Into<GodotString>> From<(S, VariantType)> for SignalProperty {
 }
 
 macro_rules! gen_constructors {
-    ($($name:ident),* $(,)?) => {
+    ($($name:ident => $type_:ident),* $(,)?) => {
         $(
             #[doc = concat!(" Create a new signal property of type `", stringify!($name), "`")]
-            pub fn $name(name: impl Into<GodotString>) -> Self {
+            pub fn $name(name: impl Into<GString>) -> Self {
                 Self {
                     name: name.into(),
-                    type_: VariantType::$name
+                    type_: VariantType::$type_
                 }
             }
         )*
@@ -123,49 +132,50 @@ macro_rules! gen_constructors {
 #[allow(non_snake_case)]
 impl SignalProperty {
     gen_constructors!(
-        Nil,
-        Bool,
-        Int,
-        Float,
-        String,
-        Vector2,
-        Vector2i,
-        Rect2,
-        Rect2i,
-        Vector3,
-        Vector3i,
-        Transform2D,
-        Vector4,
-        Vector4i,
-        Plane,
-        Quaternion,
-        Aabb,
-        Basis,
-        Transform3D,
-        Projection,
-        Color,
-        StringName,
-        NodePath,
-        Rid,
-        Object,
-        Callable,
-        Signal,
-        Dictionary,
-        Array,
-        PackedByteArray,
-        PackedInt32Array,
-        PackedInt64Array,
-        PackedFloat32Array,
-        PackedFloat64Array,
-        PackedStringArray,
-        PackedVector2Array,
-        PackedVector3Array,
-        PackedColorArray,
+        Nil => NIL,
+        Bool => BOOL,
+        Int => INT,
+        Float => FLOAT,
+        String => STRING,
+        Vector2 => VECTOR2,
+        Vector2i => VECTOR2I,
+        Rect2 => RECT2,
+        Rect2i => RECT2I,
+        Vector3 => VECTOR3,
+        Vector3i => VECTOR3I,
+        Transform2D => TRANSFORM2D,
+        Vector4 => VECTOR4,
+        Vector4i => VECTOR4I,
+        Plane => PLANE,
+        Quaternion => QUATERNION,
+        Aabb => AABB,
+        Basis => BASIS,
+        Transform3D => TRANSFORM3D,
+        Projection => PROJECTION,
+        Color => COLOR,
+        StringName => STRING_NAME,
+        NodePath => NODE_PATH,
+        Rid => RID,
+        Object => OBJECT,
+        Callable => CALLABLE,
+        Signal => SIGNAL,
+        Dictionary => DICTIONARY,
+        Array => ARRAY,
+        PackedByteArray => PACKED_BYTE_ARRAY,
+        PackedInt32Array => PACKED_INT32_ARRAY,
+        PackedInt64Array => PACKED_INT64_ARRAY,
+        PackedFloat32Array => PACKED_FLOAT32_ARRAY,
+        PackedFloat64Array => PACKED_FLOAT64_ARRAY,
+        PackedStringArray => PACKED_STRING_ARRAY,
+        PackedVector2Array => PACKED_VECTOR2_ARRAY,
+        PackedVector3Array => PACKED_VECTOR3_ARRAY,
+        PackedColorArray => PACKED_COLOR_ARRAY,
     );
 }
 
 pub trait SignalTrait<T> {
     /// Connect a signal with custom flags.
+    #[track_caller]
     fn connect_flags<C, Args, R>(
         &self,
         signal_name: impl Into<StringName>,
@@ -176,6 +186,7 @@ pub trait SignalTrait<T> {
         C: IntoCallable<Args, R>;
 
     /// Connect a signal with no flags set.
+    #[track_caller]
     fn connect_default<C, Args, R>(
         &self,
         signal_name: impl Into<StringName>,
@@ -187,20 +198,71 @@ pub trait SignalTrait<T> {
         self.connect_flags(signal_name, callable, ConnectFlags::default())
     }
 
+    /// Connect a closure that runs at most once, the connection is removed after the first emission.
+    #[track_caller]
+    fn connect_once<F, Args, R>(
+        &self,
+        signal_name: impl Into<StringName>,
+        func: F,
+    ) -> Result<(), global::Error>
+    where
+        Closure<Once<F>>: IntoCallable<Args, R>,
+    {
+        self.connect_flags(signal_name, once(func), ConnectFlags::ONE_SHOT)
+    }
+
+    /// Disconnect a callable from a signal.
+    ///
+    /// A closure can only be disconnected this way if it has the same key as the connected closure, see
+    /// [`Closure::keyed`].
+    fn disconnect_callable<C, Args, R>(&self, signal_name: impl Into<StringName>, callable: C)
+    where
+        C: IntoCallable<Args, R>;
+
+    /// Whether a callable is connected to a signal.
+    ///
+    /// A closure is only considered connected if it has the same key as a connected closure, see
+    /// [`Closure::keyed`].
+    fn is_callable_connected<C, Args, R>(
+        &self,
+        signal_name: impl Into<StringName>,
+        callable: C,
+    ) -> bool
+    where
+        C: IntoCallable<Args, R>;
+
+    /// Create a future that resolves with the arguments of the next emission of a signal, like `await` in
+    /// gdscript.
+    ///
+    /// The arguments are given as a tuple, such as `(i32,)` for a signal with one integer argument. The future
+    /// resolves to an error if the object is freed before the signal is emitted.
+    fn signal_future<A: SignalArgs>(&self, signal_name: impl Into<StringName>) -> SignalFuture<A>;
+
+    /// Create a stream of the arguments of every emission of a signal, from now until the stream is dropped.
+    fn signal_stream<A: SignalArgs>(&self, signal_name: impl Into<StringName>) -> SignalStream<A>;
+
+    /// Create a channel receiving the arguments of every emission of a signal, from now until the receiver is
+    /// dropped.
+    fn signal_receiver<A: SignalArgs>(
+        &self,
+        signal_name: impl Into<StringName>,
+    ) -> SignalReceiver<A>;
+
     /// Add a signal with the given properties.
     fn add_signal<S: Into<SignalProperty>, I: IntoIterator<Item = S>>(
         &self,
-        name: impl Into<GodotString>,
+        name: impl Into<GString>,
         properties: I,
     );
 
     /// Add a signal that takes no properties.
-    fn add_signal_none(&self, name: impl Into<GodotString>) {
+    fn add_signal_none(&self, name: impl Into<GString>) {
         self.add_signal::<SignalProperty, [SignalProperty; 0]>(name, [])
     }
 }
 
 impl<T: GodotClass + Inherits<Object>> SignalTrait<T> for Gd<T> {
+    #[track_caller]
     fn connect_flags<C, Args, R>(
         &self,
         signal_name: impl Into<StringName>,
@@ -210,11 +272,13 @@ impl<T: GodotClass + Inherits<Object>> SignalTrait<T> for Gd<T> {
     where
         C: IntoCallable<Args, R>,
     {
-        let result = self.share().upcast::<Object>().connect(
-            signal_name.into(),
-            callable.into_callable(),
-            flags.into(),
-        );
+        let flags = flags.and(C::required_flags());
+        let result = self
+            .clone()
+            .upcast::<Object>()
+            .connect_ex(&signal_name.into(), &callable.into_callable())
+            .flags(flags.into())
+            .done();
         if result == global::Error::OK {
             Ok(())
         } else {
@@ -222,17 +286,56 @@ impl<T: GodotClass + Inherits<Object>> SignalTrait<T> for Gd<T> {
         }
     }
 
+    fn disconnect_callable<C, Args, R>(&self, signal_name: impl Into<StringName>, callable: C)
+    where
+        C: IntoCallable<Args, R>,
+    {
+        self.clone()
+            .upcast::<Object>()
+            .disconnect(&signal_name.into(), &callable.into_callable())
+    }
+
+    fn is_callable_connected<C, Args, R>(
+        &self,
+        signal_name: impl Into<StringName>,
+        callable: C,
+    ) -> bool
+    where
+        C: IntoCallable<Args, R>,
+    {
+        self.clone()
+            .upcast::<Object>()
+            .is_connected(&signal_name.into(), &callable.into_callable())
+    }
+
+    fn signal_future<A: SignalArgs>(&self, signal_name: impl Into<StringName>) -> SignalFuture<A> {
+        SignalFuture::new(self, signal_name.into())
+    }
+
+    fn signal_stream<A: SignalArgs>(&self, signal_name: impl Into<StringName>) -> SignalStream<A> {
+        SignalStream::new(self, signal_name.into())
+    }
+
+    fn signal_receiver<A: SignalArgs>(
+        &self,
+        signal_name: impl Into<StringName>,
+    ) -> SignalReceiver<A> {
+        SignalReceiver::new(self, signal_name.into())
+    }
+
     fn add_signal<S: Into<SignalProperty>, I: IntoIterator<Item = S>>(
         &self,
-        name: impl Into<GodotString>,
+        name: impl Into<GString>,
         properties: I,
     ) {
-        self.share().upcast::<Object>().add_user_signal(
-            name.into(),
-            properties
-                .into_iter()
-                .map(|s| s.into().to_variant())
-                .collect(),
-        )
+        let arguments = properties
+            .into_iter()
+            .map(|s| s.into().to_variant())
+            .collect();
+        self.clone()
+            .upcast::<Object>()
+            .add_user_signal_ex(&name.into())
+            .arguments(&arguments)
+            .done()
     }
 }
@@ -0,0 +1,176 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::task::{Context, Poll, Waker};
+
+use futures_core::Stream;
+
+use super::closures::Closure;
+use super::signal_future::{Connection, SignalArgs, SignalError};
+use super::signal_inner::ConnectFlags;
+use godot::prelude::*;
+
+/// The state shared between a signal stream and the closure connected to the signal.
+struct Shared<A> {
+    /// Emissions that have not been taken from the stream yet.
+    queue: VecDeque<Result<A, SignalError>>,
+    /// Whether the closure was dropped, which means it will never be called again.
+    closure_dropped: bool,
+    waker: Option<Waker>,
+}
+
+/// Captured by the closure connected to the signal, wakes the stream on every emission and when the closure
+/// is dropped.
+struct Sender<A> {
+    shared: Rc<RefCell<Shared<A>>>,
+}
+
+impl<A> Sender<A> {
+    fn send(&self, item: Result<A, SignalError>) {
+        let waker = {
+            let mut shared = self.shared.borrow_mut();
+            shared.queue.push_back(item);
+            shared.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<A> Drop for Sender<A> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut shared = self.shared.borrow_mut();
+            shared.closure_dropped = true;
+            shared.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A stream of the arguments of every emission of a signal, see [`SignalTrait::signal_stream`].
+///
+/// Emissions whose arguments cannot be converted are yielded as errors. The stream ends when the object is
+/// freed or the signal is disconnected, and dropping the stream disconnects from the signal.
+///
+/// [`SignalTrait::signal_stream`]: crate::signal_inner::SignalTrait::signal_stream
+pub struct SignalStream<A> {
+    shared: Rc<RefCell<Shared<A>>>,
+    connection: Connection,
+}
+
+impl<A: SignalArgs> SignalStream<A> {
+    pub(crate) fn new<T>(object: &Gd<T>, signal_name: StringName) -> Self
+    where
+        T: GodotClass + Inherits<Object>,
+    {
+        let shared = Rc::new(RefCell::new(Shared {
+            queue: VecDeque::new(),
+            closure_dropped: false,
+            waker: None,
+        }));
+
+        let sender = Sender {
+            shared: Rc::clone(&shared),
+        };
+        let closure = Closure::new(move |args: &[Variant]| sender.send(A::from_args(args)))
+            .named(format!("stream {signal_name}"));
+        let (connection, result) =
+            Connection::connect(object, signal_name, closure, ConnectFlags::default());
+        if let Err(err) = result {
+            let mut shared = shared.borrow_mut();
+            shared.queue.push_back(Err(SignalError::Connect(err)));
+            shared.closure_dropped = true;
+        }
+
+        Self { shared, connection }
+    }
+
+    /// Wait for the next emission, returns `None` once the stream has ended.
+    pub fn next_emission(&mut self) -> impl Future<Output = Option<Result<A, SignalError>>> + '_ {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx))
+    }
+}
+
+impl<A: SignalArgs> Stream for SignalStream<A> {
+    type Item = Result<A, SignalError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut shared = self.shared.borrow_mut();
+        if let Some(item) = shared.queue.pop_front() {
+            return Poll::Ready(Some(item));
+        }
+        if shared.closure_dropped || self.connection.object_freed() {
+            return Poll::Ready(None);
+        }
+
+        shared.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.shared.borrow().queue.len(), None)
+    }
+}
+
+/// A channel receiving the arguments of every emission of a signal, see [`SignalTrait::signal_receiver`].
+///
+/// Emissions whose arguments cannot be converted are received as errors. The sending half is dropped when
+/// the object is freed or the signal is disconnected, and dropping the receiver disconnects from the signal.
+///
+/// The receiver can be sent to other threads, which is what blocking on it is meant for. Signals are emitted
+/// on the main thread, so calling [`recv`](mpsc::Receiver::recv) on the main thread blocks forever, use
+/// [`try_recv`](mpsc::Receiver::try_recv) there instead. Dropping the receiver on another thread disconnects
+/// from the signal on the main thread at idle time.
+///
+/// [`SignalTrait::signal_receiver`]: crate::signal_inner::SignalTrait::signal_receiver
+pub struct SignalReceiver<A> {
+    receiver: mpsc::Receiver<Result<A, SignalError>>,
+    _connection: Connection,
+}
+
+impl<A: SignalArgs> SignalReceiver<A> {
+    pub(crate) fn new<T>(object: &Gd<T>, signal_name: StringName) -> Self
+    where
+        T: GodotClass + Inherits<Object>,
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        let connect_error = sender.clone();
+        let closure = Closure::new(move |args: &[Variant]| {
+            // The receiver disconnects when it is dropped, so sending can only fail while that happens.
+            let _ = sender.send(A::from_args(args));
+        })
+        .named(format!("receive {signal_name}"));
+        let (connection, result) =
+            Connection::connect(object, signal_name, closure, ConnectFlags::default());
+        if let Err(err) = result {
+            let _ = connect_error.send(Err(SignalError::Connect(err)));
+        }
+
+        Self {
+            receiver,
+            _connection: connection,
+        }
+    }
+}
+
+impl<A> Deref for SignalReceiver<A> {
+    type Target = mpsc::Receiver<Result<A, SignalError>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
@@ -0,0 +1,164 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+use godot::prelude::*;
+
+use crate::closures::{self, Closure};
+use crate::prelude::CallableStatic;
+use crate::IntoCallable;
+
+/// Why calling a [`TypedCallable`] failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallError {
+    /// The callable is not valid, for instance because its object was freed.
+    InvalidCallable,
+    /// The callable takes a different number of arguments than its signature.
+    ArgumentCount { expected: usize, actual: usize },
+    /// The callable returned a value that could not be converted to the expected return type.
+    ReturnConversion {
+        expected: &'static str,
+        actual: VariantType,
+    },
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCallable => write!(f, "the callable is not valid"),
+            Self::ArgumentCount { expected, actual } => write!(
+                f,
+                "expected the callable to take {expected} arguments, but it takes {actual}"
+            ),
+            Self::ReturnConversion { expected, actual } => write!(
+                f,
+                "expected the callable to return `{expected}`, but it returned a value of type {actual:?}"
+            ),
+        }
+    }
+}
+
+impl Error for CallError {}
+
+/// A callable with known argument and return types, given as a tuple of the argument types and the return
+/// type, such as `TypedCallable<(i32, GString), bool>`.
+///
+/// Closures can be turned into a typed callable with [`TypedCallable::from_fn`], which infers the types from the
+/// closure. They can also be converted with `From`, but a closure converted with `into()` records a location in
+/// the standard library instead of the caller.
+pub struct TypedCallable<Args, R> {
+    callable: Callable,
+    _signature: PhantomData<fn(Args) -> R>,
+}
+
+impl<Args, R> TypedCallable<Args, R> {
+    /// Wrap a callable without checking that it has the given signature.
+    pub fn from_callable_unchecked(callable: Callable) -> Self {
+        Self {
+            callable,
+            _signature: PhantomData,
+        }
+    }
+
+    /// Wrap a closure, remembering where this was called as the location of the closure.
+    #[track_caller]
+    pub fn from_fn<F>(func: F) -> Self
+    where
+        Closure<F>: IntoCallable<Args, R>,
+    {
+        Self::from_callable_unchecked(Closure::new(func).into_callable())
+    }
+
+    /// The underlying callable.
+    pub fn callable(&self) -> &Callable {
+        &self.callable
+    }
+
+    /// Unwrap the underlying callable.
+    pub fn into_inner(self) -> Callable {
+        self.callable
+    }
+}
+
+impl<Args, R> fmt::Debug for TypedCallable<Args, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedCallable")
+            .field(&self.callable)
+            .finish()
+    }
+}
+
+impl<Args, R> Clone for TypedCallable<Args, R> {
+    fn clone(&self) -> Self {
+        Self::from_callable_unchecked(self.callable.clone())
+    }
+}
+
+impl<Args, R> PartialEq for TypedCallable<Args, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.callable == other.callable
+    }
+}
+
+impl<Args, R> IntoCallable<(), ()> for TypedCallable<Args, R> {
+    fn into_callable(self) -> Callable {
+        self.callable
+    }
+}
+
+fn convert_return<R: FromGodot>(ret: Variant) -> Result<R, CallError> {
+    ret.try_to::<R>().map_err(|_| CallError::ReturnConversion {
+        expected: std::any::type_name::<R>(),
+        actual: ret.get_type(),
+    })
+}
+
+macro_rules! impl_typed_callable {
+    ($n:literal $($bind:ident)?; [$(($index:literal $arg:ident $generic:ident))*]; [$($init:tt)*]) => {
+        impl<$($generic: ToGodot,)* R: FromGodot> TypedCallable<($($generic,)*), R> {
+            #[doc = concat!(" Call the underlying callable with ", stringify!($n), " argument(s).")]
+            ///
+            /// The callable is checked to be valid and to take as many arguments as the signature before it is
+            /// called. Callables that do not report their argument count are called regardless. Any other error
+            /// godot reports for the call is printed by godot, and the call returns `Nil`, which is only
+            /// reported as [`CallError::ReturnConversion`] if `Nil` cannot be converted to `R`.
+            #[allow(clippy::too_many_arguments)]
+            pub fn call(&self, $($arg: $generic),*) -> Result<R, CallError> {
+                if !self.callable.is_valid() {
+                    return Err(CallError::InvalidCallable);
+                }
+                if let Some(actual) = closures::argument_count(&self.callable) {
+                    if actual != $n {
+                        return Err(CallError::ArgumentCount { expected: $n, actual });
+                    }
+                }
+                convert_return(self.callable.callv(&varray![$($arg.to_variant()),*]))
+            }
+        }
+
+        impl<F, R, $($generic),*> From<F> for TypedCallable<($($generic,)*), R>
+        where
+            F: FnMut($($generic),*) -> R + 'static,
+            R: ToGodot + 'static,
+            $($generic: FromGodot + 'static),*
+        {
+            #[track_caller]
+            fn from(func: F) -> Self {
+                Self::from_fn(func)
+            }
+        }
+
+        impl<$($generic,)* R> From<TypedCallable<($($generic,)*), R>> for CallableStatic<$n> {
+            fn from(typed: TypedCallable<($($generic,)*), R>) -> Self {
+                CallableStatic(typed.callable)
+            }
+        }
+    };
+}
+
+for_each_arity!(impl_typed_callable);
@@ -0,0 +1,548 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+use super::executor;
+use super::signal_future::{SignalError, SignalFuture};
+use super::signal_inner::SignalTrait;
+use godot::prelude::*;
+
+/// A future that finishes after some time has passed, see [`sleep`].
+pub struct Sleep {
+    timeout: SignalFuture<()>,
+}
+
+/// Wait for `seconds` seconds, using a timer of the scene tree.
+///
+/// The timer keeps running while the scene tree is paused, and is affected by the time scale.
+///
+/// # Panics
+///
+/// If the main loop is not a `SceneTree`.
+pub fn sleep(seconds: f64) -> Sleep {
+    let mut tree = executor::scene_tree()
+        .expect("timers can only be created while the main loop is a `SceneTree`");
+    let timer = tree
+        .create_timer(seconds)
+        .expect("the scene tree failed to create a timer");
+
+    Sleep {
+        timeout: timer.signal_future("timeout"),
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.timeout).poll(cx).map(|result| {
+            // The timer can only be freed once it has timed out, so anything but a connect error means the time
+            // has passed. After a connect error the timer never times out, so this finishes right away instead.
+            if let Err(SignalError::Connect(err)) = result {
+                godot_error!("failed to connect to the timeout of a timer: {err:?}");
+            }
+        })
+    }
+}
+
+/// The error returned when a future did not finish in time, see [`timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the future did not finish in time")
+    }
+}
+
+impl Error for Elapsed {}
+
+/// A future with a time limit, see [`timeout`].
+pub struct Timeout<F> {
+    future: Pin<Box<F>>,
+    sleep: Sleep,
+}
+
+/// Wait for `future` for at most `seconds` seconds, see [`sleep`].
+///
+/// When the time runs out first, `future` is dropped, which disconnects it from any signal it was waiting for.
+pub fn timeout<F: Future>(seconds: f64, future: F) -> Timeout<F> {
+    Timeout {
+        future: Box::pin(future),
+        sleep: sleep(seconds),
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        Pin::new(&mut self.sleep).poll(cx).map(|()| Err(Elapsed))
+    }
+}
+
+/// The output of the future that finished first, see [`select`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+/// Two futures of which only the first to finish is used, see [`select`].
+pub struct Select<A, B> {
+    a: Pin<Box<A>>,
+    b: Pin<Box<B>>,
+}
+
+/// Wait for whichever of two futures finishes first, the other one is dropped.
+///
+/// When both are ready at the same time, `a` wins.
+pub fn select<A: Future, B: Future>(a: A, b: B) -> Select<A, B> {
+    Select {
+        a: Box::pin(a),
+        b: Box::pin(b),
+    }
+}
+
+impl<A: Future, B: Future> Future for Select<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.a.as_mut().poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        self.b.as_mut().poll(cx).map(Either::Right)
+    }
+}
+
+/// Futures of which only the first to finish is used, see [`select_all`].
+pub struct SelectAll<F> {
+    futures: Vec<Pin<Box<F>>>,
+}
+
+/// Wait for whichever of several futures finishes first, resolving to its index and output. The other
+/// futures are dropped.
+///
+/// # Panics
+///
+/// If there are no futures.
+pub fn select_all<F: Future>(futures: impl IntoIterator<Item = F>) -> SelectAll<F> {
+    let futures: Vec<_> = futures.into_iter().map(Box::pin).collect();
+    assert!(
+        !futures.is_empty(),
+        "`select_all` needs at least one future"
+    );
+    SelectAll { futures }
+}
+
+impl<F: Future> Future for SelectAll<F> {
+    type Output = (usize, F::Output);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        for (index, future) in self.futures.iter_mut().enumerate() {
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                return Poll::Ready((index, output));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// A future that is polled until it finishes, after which its output is kept until it is taken.
+enum MaybeDone<F: Future> {
+    Pending(Pin<Box<F>>),
+    Done(F::Output),
+    Taken,
+}
+
+impl<F: Future> MaybeDone<F> {
+    /// Poll the future if it has not finished yet, returns whether it has.
+    fn poll(&mut self, cx: &mut Context<'_>) -> bool {
+        if let Self::Pending(future) = self {
+            match future.as_mut().poll(cx) {
+                Poll::Ready(output) => *self = Self::Done(output),
+                Poll::Pending => return false,
+            }
+        }
+        true
+    }
+
+    fn take(&mut self) -> F::Output {
+        match mem::replace(self, Self::Taken) {
+            Self::Done(output) => output,
+            _ => unreachable!("the output of a future was taken before it finished"),
+        }
+    }
+}
+
+/// Two futures that are both waited for, see [`join`].
+pub struct Join<A: Future, B: Future> {
+    a: MaybeDone<A>,
+    b: MaybeDone<B>,
+}
+
+/// Wait for both futures to finish, resolving to both of their outputs.
+pub fn join<A: Future, B: Future>(a: A, b: B) -> Join<A, B> {
+    Join {
+        a: MaybeDone::Pending(Box::pin(a)),
+        b: MaybeDone::Pending(Box::pin(b)),
+    }
+}
+
+// The futures are boxed, so they are never moved while pinned.
+impl<A: Future, B: Future> Unpin for Join<A, B> {}
+
+impl<A: Future, B: Future> Future for Join<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let a_done = self.a.poll(cx);
+        let b_done = self.b.poll(cx);
+        if a_done && b_done {
+            Poll::Ready((self.a.take(), self.b.take()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Futures that are all waited for, see [`join_all`].
+pub struct JoinAll<F: Future> {
+    futures: Vec<MaybeDone<F>>,
+}
+
+/// Wait for all futures to finish, resolving to their outputs in the same order.
+pub fn join_all<F: Future>(futures: impl IntoIterator<Item = F>) -> JoinAll<F> {
+    JoinAll {
+        futures: futures
+            .into_iter()
+            .map(|future| MaybeDone::Pending(Box::pin(future)))
+            .collect(),
+    }
+}
+
+// The futures are boxed, so they are never moved while pinned.
+impl<F: Future> Unpin for JoinAll<F> {}
+
+impl<F: Future> Future for JoinAll<F> {
+    type Output = Vec<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut all_done = true;
+        for future in &mut self.futures {
+            all_done &= future.poll(cx);
+        }
+
+        if all_done {
+            Poll::Ready(self.futures.iter_mut().map(MaybeDone::take).collect())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// The error returned when waiting was cancelled, see [`CancellationToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the future was cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    /// The tasks waiting for futures run with the token, by the id of the future.
+    wakers: Mutex<HashMap<u64, Waker>>,
+    next_id: AtomicU64,
+}
+
+/// Cancels waiting for futures that were run with it.
+///
+/// Clones of a token refer to the same token, so any of them can cancel it. A token can be cancelled from any
+/// thread.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<TokenState>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel all futures run with this token, now and in the future.
+    ///
+    /// Their tasks are woken, and each future is dropped the next time it is polled. This disconnects it from
+    /// any signal it was waiting for.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+        let wakers = mem::take(&mut *self.wakers());
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// Whether [`CancellationToken::cancel`] has been called on this token or any of its clones.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Wait for `future` unless this token is cancelled first.
+    pub fn run<F: Future>(&self, future: F) -> Cancellable<F> {
+        Cancellable {
+            future: Some(Box::pin(future)),
+            token: self.clone(),
+            id: self.0.next_id.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    fn wakers(&self) -> MutexGuard<'_, HashMap<u64, Waker>> {
+        self.0.wakers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wake `waker` when this token is cancelled, instead of the waker last registered for the future `id`.
+    fn register(&self, id: u64, waker: &Waker) {
+        let mut wakers = self.wakers();
+        match wakers.get_mut(&id) {
+            Some(registered) if registered.will_wake(waker) => {}
+            Some(registered) => registered.clone_from(waker),
+            None => {
+                wakers.insert(id, waker.clone());
+            }
+        }
+    }
+
+    /// Stop waking the task of the future `id`, as it no longer waits.
+    fn unregister(&self, id: u64) {
+        self.wakers().remove(&id);
+    }
+}
+
+/// A future that can be cancelled, see [`CancellationToken::run`].
+pub struct Cancellable<F> {
+    /// `None` once the future has been cancelled.
+    future: Option<Pin<Box<F>>>,
+    token: CancellationToken,
+    /// Identifies the waker of this future in the token.
+    id: u64,
+}
+
+impl<F: Future> Future for Cancellable<F> {
+    type Output = Result<F::Output, Cancelled>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.token.is_cancelled() {
+            self.future = None;
+            return Poll::Ready(Err(Cancelled));
+        }
+        let Some(future) = self.future.as_mut() else {
+            return Poll::Ready(Err(Cancelled));
+        };
+
+        match future.as_mut().poll(cx) {
+            Poll::Ready(output) => {
+                self.token.unregister(self.id);
+                Poll::Ready(Ok(output))
+            }
+            Poll::Pending => {
+                self.token.register(self.id, cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<F> Drop for Cancellable<F> {
+    fn drop(&mut self) {
+        self.token.unregister(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{pending, ready};
+    use std::sync::atomic::AtomicUsize;
+    use std::task::Wake;
+
+    use super::*;
+
+    /// Counts how often it is woken.
+    #[derive(Default)]
+    struct CountWaker(AtomicUsize);
+
+    impl CountWaker {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Wake for CountWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn poll_with<F: Future + Unpin>(future: &mut F, waker: &Arc<CountWaker>) -> Poll<F::Output> {
+        let waker = Waker::from(Arc::clone(waker));
+        Pin::new(future).poll(&mut Context::from_waker(&waker))
+    }
+
+    fn poll_once<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+        poll_with(future, &Arc::default())
+    }
+
+    /// A future that is pending for the given number of polls, and then resolves to `output`.
+    struct PendingFor<T> {
+        polls: usize,
+        output: Option<T>,
+    }
+
+    fn pending_for<T>(polls: usize, output: T) -> PendingFor<T> {
+        PendingFor {
+            polls,
+            output: Some(output),
+        }
+    }
+
+    impl<T: Unpin> Future for PendingFor<T> {
+        type Output = T;
+
+        fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<T> {
+            if self.polls > 0 {
+                self.polls -= 1;
+                return Poll::Pending;
+            }
+            Poll::Ready(self.output.take().expect("polled after it finished"))
+        }
+    }
+
+    #[test]
+    fn select_prefers_the_first_ready_future() {
+        assert_eq!(
+            poll_once(&mut select(ready(1), ready(2))),
+            Poll::Ready(Either::Left(1))
+        );
+        assert_eq!(
+            poll_once(&mut select(pending::<i32>(), ready(2))),
+            Poll::Ready(Either::Right(2))
+        );
+        assert_eq!(
+            poll_once(&mut select(pending::<i32>(), pending::<i32>())),
+            Poll::Pending
+        );
+    }
+
+    #[test]
+    fn select_resolves_to_the_future_that_finishes_first() {
+        let mut select = select(pending_for(2, "a"), pending_for(1, "b"));
+        assert_eq!(poll_once(&mut select), Poll::Pending);
+        assert_eq!(poll_once(&mut select), Poll::Ready(Either::Right("b")));
+    }
+
+    #[test]
+    fn select_all_prefers_the_lowest_index() {
+        let futures = [pending_for(1, 0), pending_for(0, 1), pending_for(0, 2)];
+        assert_eq!(poll_once(&mut select_all(futures)), Poll::Ready((1, 1)));
+
+        let mut select = select_all([pending_for(3, 0), pending_for(2, 1), pending_for(2, 2)]);
+        assert_eq!(poll_once(&mut select), Poll::Pending);
+        assert_eq!(poll_once(&mut select), Poll::Pending);
+        assert_eq!(poll_once(&mut select), Poll::Ready((1, 1)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_all_needs_a_future() {
+        select_all(Vec::<PendingFor<()>>::new());
+    }
+
+    #[test]
+    fn join_all_keeps_the_order_of_the_futures() {
+        let mut join = join_all([
+            pending_for(2, 'a'),
+            pending_for(0, 'b'),
+            pending_for(1, 'c'),
+        ]);
+        assert_eq!(poll_once(&mut join), Poll::Pending);
+        assert_eq!(poll_once(&mut join), Poll::Pending);
+        assert_eq!(poll_once(&mut join), Poll::Ready(vec!['a', 'b', 'c']));
+    }
+
+    #[test]
+    fn join_waits_for_both_futures() {
+        let mut join = join(pending_for(0, 1), pending_for(1, "two"));
+        assert_eq!(poll_once(&mut join), Poll::Pending);
+        assert_eq!(poll_once(&mut join), Poll::Ready((1, "two")));
+    }
+
+    #[test]
+    fn cancel_before_the_first_poll() {
+        let token = CancellationToken::new();
+        let mut future = token.run(ready(1));
+        token.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(poll_once(&mut future), Poll::Ready(Err(Cancelled)));
+    }
+
+    #[test]
+    fn cancel_after_the_first_poll() {
+        let token = CancellationToken::new();
+        let waker = Arc::default();
+        let mut future = token.run(pending::<i32>());
+        assert_eq!(poll_with(&mut future, &waker), Poll::Pending);
+        assert_eq!(waker.count(), 0);
+
+        token.clone().cancel();
+        assert_eq!(waker.count(), 1);
+        assert_eq!(poll_with(&mut future, &waker), Poll::Ready(Err(Cancelled)));
+    }
+
+    #[test]
+    fn cancellable_runs_the_future_until_cancelled() {
+        let token = CancellationToken::new();
+        let mut future = token.run(pending_for(1, 1));
+        assert_eq!(poll_once(&mut future), Poll::Pending);
+        assert_eq!(poll_once(&mut future), Poll::Ready(Ok(1)));
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn finished_and_dropped_futures_are_not_woken() {
+        let token = CancellationToken::new();
+        let (finished_waker, dropped_waker) = (Arc::default(), Arc::default());
+
+        let mut finished = token.run(pending_for(1, ()));
+        assert_eq!(poll_with(&mut finished, &finished_waker), Poll::Pending);
+        assert_eq!(
+            poll_with(&mut finished, &finished_waker),
+            Poll::Ready(Ok(()))
+        );
+
+        let mut dropped = token.run(pending::<()>());
+        assert_eq!(poll_with(&mut dropped, &dropped_waker), Poll::Pending);
+        drop(dropped);
+
+        assert!(token.wakers().is_empty());
+        token.cancel();
+        assert_eq!(finished_waker.count(), 0);
+        assert_eq!(dropped_waker.count(), 0);
+    }
+}